@@ -1,7 +1,11 @@
 use crate::{
-    Dataset, Error, FeatureData, Parameters, Result,
-    mat::AsMat,
+    Dataset, Error, FeatureData, Field, IndPtrData, MatBuf, Parameters, PredictType, Prediction,
+    Result, SparseMat,
+    mat::{AsMat, AsSparseMat, Csc, Csr, RowMajor, SparseLayout},
+    model::ModelDump,
+    parameters::Metric,
     to_result,
+    train::Objective,
     utils::{get_cstring, get_strings, int_to_bool, path_to_cstring},
 };
 use lgbm_sys::{
@@ -10,34 +14,35 @@ use lgbm_sys::{
     C_API_PREDICT_NORMAL, C_API_PREDICT_RAW_SCORE, LGBM_BoosterAddValidData,
     LGBM_BoosterCalcNumPredict, LGBM_BoosterCreate, LGBM_BoosterCreateFromModelfile,
     LGBM_BoosterDumpModel, LGBM_BoosterFeatureImportance, LGBM_BoosterFree,
-    LGBM_BoosterGetCurrentIteration, LGBM_BoosterGetEval, LGBM_BoosterGetEvalCounts,
-    LGBM_BoosterGetEvalNames, LGBM_BoosterGetFeatureNames, LGBM_BoosterGetNumClasses,
-    LGBM_BoosterGetNumFeature, LGBM_BoosterGetNumPredict, LGBM_BoosterGetPredict,
-    LGBM_BoosterLoadModelFromString, LGBM_BoosterNumModelPerIteration,
-    LGBM_BoosterNumberOfTotalModel, LGBM_BoosterPredictForMat, LGBM_BoosterRollbackOneIter,
-    LGBM_BoosterSaveModel, LGBM_BoosterSaveModelToString, LGBM_BoosterUpdateOneIter,
-    LGBM_BoosterUpdateOneIterCustom,
+    LGBM_BoosterFreePredictSparse, LGBM_BoosterGetCurrentIteration, LGBM_BoosterGetEval,
+    LGBM_BoosterGetEvalCounts, LGBM_BoosterGetEvalNames, LGBM_BoosterGetFeatureNames,
+    LGBM_BoosterGetLeafValue, LGBM_BoosterGetLoadedParam, LGBM_BoosterGetLowerBoundValue,
+    LGBM_BoosterGetNumClasses, LGBM_BoosterGetNumFeature, LGBM_BoosterGetNumPredict,
+    LGBM_BoosterGetPredict, LGBM_BoosterGetUpperBoundValue, LGBM_BoosterLoadModelFromString,
+    LGBM_BoosterMerge, LGBM_BoosterNumModelPerIteration, LGBM_BoosterNumberOfTotalModel,
+    LGBM_BoosterPredictForCSC, LGBM_BoosterPredictForCSR, LGBM_BoosterPredictForMat,
+    LGBM_BoosterPredictSparseOutput, LGBM_BoosterRefit, LGBM_BoosterResetParameter,
+    LGBM_BoosterResetTrainingData, LGBM_BoosterRollbackOneIter, LGBM_BoosterSaveModel,
+    LGBM_BoosterSaveModelToString, LGBM_BoosterSetLeafValue, LGBM_BoosterShuffleModels,
+    LGBM_BoosterUpdateOneIter, LGBM_BoosterUpdateOneIterCustom,
 };
-use serde::{Deserialize, Serialize};
 use std::{
     ffi::{CStr, CString},
-    os::raw::c_int,
+    os::raw::{c_int, c_void},
     path::Path,
     ptr::null_mut,
-    sync::Arc,
+    slice,
+    sync::{Arc, Mutex, PoisonError},
 };
-use text_grid::{Cells, cells_f, cells_schema, to_grid_with_schema};
 
-#[repr(u32)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum PredictType {
-    Normal = C_API_PREDICT_NORMAL,
-    RawScore = C_API_PREDICT_RAW_SCORE,
-    LeafIndex = C_API_PREDICT_LEAF_INDEX,
-    Contrib = C_API_PREDICT_CONTRIB,
-}
+const _: () = {
+    assert!(PredictType::Normal as u32 == C_API_PREDICT_NORMAL);
+    assert!(PredictType::RawScore as u32 == C_API_PREDICT_RAW_SCORE);
+    assert!(PredictType::LeafIndex as u32 == C_API_PREDICT_LEAF_INDEX);
+    assert!(PredictType::Contrib as u32 == C_API_PREDICT_CONTRIB);
+};
 impl PredictType {
-    fn to_cint(self) -> c_int {
+    pub(crate) fn to_cint(self) -> c_int {
         self as u32 as c_int
     }
 }
@@ -49,9 +54,9 @@ pub enum MatrixType {
     Csc = C_API_MATRIX_TYPE_CSC,
 }
 impl MatrixType {
-    // fn to_cint(self) -> c_int {
-    //     self as u32 as c_int
-    // }
+    fn to_cint(self) -> c_int {
+        self as u32 as c_int
+    }
 }
 
 #[repr(u32)]
@@ -66,10 +71,44 @@ impl FeatureImportanceType {
     }
 }
 
+/// Value of a metric evaluated on a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalResult {
+    /// Index of the dataset. `0` is the training dataset.
+    pub data_idx: usize,
+    pub data_name: String,
+    pub metric_name: String,
+
+    /// `None` if `metric_name` is not a built-in metric.
+    pub metric: Option<Metric>,
+    pub value: f64,
+    pub higher_better: bool,
+}
+impl EvalResult {
+    /// Create a result of a built-in metric named by [`Booster::get_eval_names`].
+    pub fn new(data_idx: usize, data_name: &str, metric_name: &str, value: f64) -> Self {
+        let metric = metric_name
+            .split('@')
+            .next()
+            .and_then(|name| name.parse::<Metric>().ok());
+        Self {
+            data_idx,
+            data_name: data_name.to_string(),
+            metric_name: metric_name.to_string(),
+            metric,
+            value,
+            higher_better: metric.is_some_and(|m| !m.is_lower_is_better()),
+        }
+    }
+}
+
 /// Owned [BoosterHandle](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.BoosterHandle)
 pub struct Booster {
-    handle: BoosterHandle,
-    data: Vec<Option<Arc<Dataset>>>,
+    pub(crate) handle: BoosterHandle,
+    pub(crate) data: Vec<Option<Arc<Dataset>>>,
+
+    /// Number of leaves of each tree, read from [`dump_model`](Self::dump_model) on demand.
+    num_leaves: Mutex<Option<Arc<[usize]>>>,
 }
 
 impl Booster {
@@ -77,6 +116,7 @@ impl Booster {
         Self {
             handle,
             data: vec![train_data],
+            num_leaves: Mutex::new(None),
         }
     }
 
@@ -94,6 +134,50 @@ impl Booster {
         Ok(Self::from_handle(handle, Some(train_data)))
     }
 
+    /// Create a booster that continues training from `init_model`.
+    ///
+    /// The init score of `train_data` is set to the raw predictions of `init_model` for `features`,
+    /// which must be the features used to construct `train_data`.
+    /// The trees of `init_model` are included in the created booster.
+    ///
+    /// The init score of validation datasets can be set by [`set_init_score`](Self::set_init_score).
+    pub fn from_init_model<T: FeatureData>(
+        init_model: &Booster,
+        mut train_data: Dataset,
+        features: impl AsMat<T>,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        init_model.set_init_score(&mut train_data, features)?;
+        let booster = Self::new(Arc::new(train_data), parameters)?;
+        unsafe {
+            to_result(LGBM_BoosterMerge(booster.handle, init_model.handle))?;
+        }
+        Ok(booster)
+    }
+
+    /// Set the init score of `dataset` to the raw predictions of this model for `features`.
+    pub fn set_init_score<T: FeatureData>(
+        &self,
+        dataset: &mut Dataset,
+        features: impl AsMat<T>,
+    ) -> Result<()> {
+        let features = features.as_mat();
+        let num_data = dataset.get_num_data()?;
+        if features.nrow() != num_data {
+            return Err(Error::from_message(&format!(
+                "row size must be {num_data}, but got {}",
+                features.nrow()
+            )));
+        }
+        let p =
+            self.predict_for_mat(features, PredictType::RawScore, 0, None, &Parameters::new())?;
+        let num_class = p.num_class();
+        let init_score: Vec<f64> = (0..num_data * num_class)
+            .map(|i| p[[i % num_data, i / num_data]])
+            .collect();
+        dataset.set_field(Field::INIT_SCORE, &init_score)
+    }
+
     /// [LGBM_BoosterCreateFromModelfile](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterCreateFromModelfile)
     #[doc(alias = "LGBM_BoosterCreateFromModelfile")]
     pub fn from_file(filename: &Path) -> Result<(Self, usize)> {
@@ -135,6 +219,21 @@ impl Booster {
         Ok(())
     }
 
+    /// [LGBM_BoosterResetTrainingData](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterResetTrainingData)
+    ///
+    /// `train_data` must have the same bin mappers as the current training dataset,
+    /// e.g. constructed with the current training dataset as the reference.
+    /// Returns an error if the booster has no training dataset, such as one loaded by [`from_file`](Self::from_file).
+    #[doc(alias = "LGBM_BoosterResetTrainingData")]
+    pub fn reset_training_data(&mut self, train_data: Arc<Dataset>) -> Result<()> {
+        if self.data(0).is_none() {
+            return Err(Error::from_message("training data is not available"));
+        }
+        unsafe { to_result(LGBM_BoosterResetTrainingData(self.handle, train_data.0))? }
+        self.data[0] = Some(train_data);
+        Ok(())
+    }
+
     /// [LGBM_BoosterCalcNumPredict](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterCalcNumPredict)
     #[doc(alias = "LGBM_BoosterCalcNumPredict")]
     pub fn calc_num_predict(
@@ -186,6 +285,104 @@ impl Booster {
         })
     }
 
+    /// Parse the result of [`dump_model`](Self::dump_model).
+    pub fn dump_model_typed(
+        &self,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        feature_importance_type: FeatureImportanceType,
+    ) -> Result<ModelDump> {
+        let model = self.dump_model(start_iteration, num_iteration, feature_importance_type)?;
+        Ok(serde_json::from_slice(model.as_bytes())?)
+    }
+
+    /// Returns the number of leaves of each tree.
+    ///
+    /// The model is dumped only once until the trees are changed.
+    fn num_leaves(&self) -> Result<Arc<[usize]>> {
+        let mut cache = self
+            .num_leaves
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(num_leaves) = &*cache {
+            return Ok(num_leaves.clone());
+        }
+        let model = self.dump_model_typed(0, None, FeatureImportanceType::Split)?;
+        let num_leaves: Arc<[usize]> = model.tree_info.iter().map(|tree| tree.num_leaves).collect();
+        *cache = Some(num_leaves.clone());
+        Ok(num_leaves)
+    }
+    fn clear_num_leaves(&mut self) {
+        *self
+            .num_leaves
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    fn check_leaf_idx(&self, tree_idx: usize, leaf_idx: usize) -> Result<()> {
+        let num_leaves = self.num_leaves()?;
+        let Some(&n) = num_leaves.get(tree_idx) else {
+            return Err(Error::from_message(&format!(
+                "tree_idx must be less than {}, but got {tree_idx}",
+                num_leaves.len()
+            )));
+        };
+        if leaf_idx >= n {
+            return Err(Error::from_message(&format!(
+                "leaf_idx must be less than {n}, but got {leaf_idx}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// [LGBM_BoosterGetLeafValue](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetLeafValue)
+    #[doc(alias = "LGBM_BoosterGetLeafValue")]
+    pub fn leaf_value(&self, tree_idx: usize, leaf_idx: usize) -> Result<f64> {
+        self.check_leaf_idx(tree_idx, leaf_idx)?;
+        self.leaf_value_unchecked(tree_idx, leaf_idx)
+    }
+    fn leaf_value_unchecked(&self, tree_idx: usize, leaf_idx: usize) -> Result<f64> {
+        let mut value = 0.0;
+        unsafe {
+            to_result(LGBM_BoosterGetLeafValue(
+                self.handle,
+                tree_idx.try_into()?,
+                leaf_idx.try_into()?,
+                &mut value,
+            ))?;
+        }
+        Ok(value)
+    }
+
+    /// [LGBM_BoosterSetLeafValue](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterSetLeafValue)
+    #[doc(alias = "LGBM_BoosterSetLeafValue")]
+    pub fn set_leaf_value(&mut self, tree_idx: usize, leaf_idx: usize, value: f64) -> Result<()> {
+        self.check_leaf_idx(tree_idx, leaf_idx)?;
+        unsafe {
+            to_result(LGBM_BoosterSetLeafValue(
+                self.handle,
+                tree_idx.try_into()?,
+                leaf_idx.try_into()?,
+                value,
+            ))
+        }
+    }
+
+    /// Returns `(tree_idx, leaf_idx, value)` for all leaves of all trees.
+    pub fn leaf_values(&self) -> Result<impl Iterator<Item = (usize, usize, f64)>> {
+        let mut values = Vec::new();
+        for (tree_idx, &num_leaves) in self.num_leaves()?.iter().enumerate() {
+            for leaf_idx in 0..num_leaves {
+                values.push((
+                    tree_idx,
+                    leaf_idx,
+                    self.leaf_value_unchecked(tree_idx, leaf_idx)?,
+                ));
+            }
+        }
+        Ok(values.into_iter())
+    }
+
     /// [LGBM_BoosterFeatureImportance](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterFeatureImportance)
     #[doc(alias = "LGBM_BoosterFeatureImportance")]
     pub fn feature_importance(
@@ -233,6 +430,31 @@ impl Booster {
         Ok(out_results)
     }
 
+    /// Evaluate the training dataset and all validation datasets added by [`add_valid_data`](Self::add_valid_data).
+    ///
+    /// The datasets are named `"training"` and `"valid_{i}"`, where `i` is the 0-based index of the validation dataset
+    /// (`data_idx - 1`), the same as the Python package.
+    /// Use [`train`](crate::train::train) to name the datasets.
+    pub fn eval_all(&self) -> Result<Vec<EvalResult>> {
+        let metric_names = self.get_eval_names()?;
+        let mut results = Vec::new();
+        for data_idx in 0..self.data.len() {
+            if self.data[data_idx].is_none() {
+                continue;
+            }
+            let data_name = if data_idx == 0 {
+                "training".to_string()
+            } else {
+                format!("valid_{}", data_idx - 1)
+            };
+            let values = self.get_eval(data_idx)?;
+            for (metric_name, value) in metric_names.iter().zip(values) {
+                results.push(EvalResult::new(data_idx, &data_name, metric_name, value));
+            }
+        }
+        Ok(results)
+    }
+
     /// [LGBM_BoosterGetEvalCounts](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetEvalCounts)
     #[doc(alias = "LGBM_BoosterGetEvalCounts")]
     fn get_eval_counts(&self) -> Result<usize> {
@@ -260,6 +482,18 @@ impl Booster {
         )
     }
 
+    /// [LGBM_BoosterGetLoadedParam](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetLoadedParam)
+    ///
+    /// Returns the parameters stored in the model loaded by [`from_file`](Self::from_file) or [`from_string`](Self::from_string).
+    /// Returns empty parameters for a booster created by [`new`](Self::new).
+    #[doc(alias = "LGBM_BoosterGetLoadedParam")]
+    pub fn loaded_parameters(&self) -> Result<Parameters> {
+        let json = get_cstring(|buffer_len, out_len, out_str| unsafe {
+            LGBM_BoosterGetLoadedParam(self.handle, buffer_len, out_len, out_str)
+        })?;
+        Parameters::from_json(json.to_str().map_err(Error::from_error)?)
+    }
+
     /// [LGBM_BoosterGetFeatureNames](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetFeatureNames)
     #[doc(alias = "LGBM_BoosterGetFeatureNames")]
     pub fn get_feature_names(&self) -> Result<Vec<String>> {
@@ -319,6 +553,57 @@ impl Booster {
         }
     }
 
+    /// [LGBM_BoosterMerge](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterMerge)
+    ///
+    /// Add the trees of `other` to this booster. The trees of `other` are placed before the trees of this booster.
+    ///
+    /// Returns an error if the number of classes, the number of models per iteration or the feature names are different.
+    #[doc(alias = "LGBM_BoosterMerge")]
+    pub fn merge(&mut self, other: &Booster) -> Result<()> {
+        let num_classes = (self.get_num_classes()?, other.get_num_classes()?);
+        if num_classes.0 != num_classes.1 {
+            return Err(Error::from_message(&format!(
+                "number of classes must be {}, but got {}",
+                num_classes.0, num_classes.1
+            )));
+        }
+        let num_model_per_iteration = (
+            self.num_model_per_iteration()?,
+            other.num_model_per_iteration()?,
+        );
+        if num_model_per_iteration.0 != num_model_per_iteration.1 {
+            return Err(Error::from_message(&format!(
+                "number of models per iteration must be {}, but got {}",
+                num_model_per_iteration.0, num_model_per_iteration.1
+            )));
+        }
+        if self.get_feature_names()? != other.get_feature_names()? {
+            return Err(Error::from_message("feature names mismatch"));
+        }
+        self.clear_num_leaves();
+        unsafe { to_result(LGBM_BoosterMerge(self.handle, other.handle)) }
+    }
+
+    /// [LGBM_BoosterShuffleModels](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterShuffleModels)
+    ///
+    /// Shuffle the order of the iterations in `start_iteration..end_iteration`.
+    /// If `end_iteration` is `None`, shuffle until the last iteration.
+    #[doc(alias = "LGBM_BoosterShuffleModels")]
+    pub fn shuffle_models(
+        &mut self,
+        start_iteration: usize,
+        end_iteration: Option<usize>,
+    ) -> Result<()> {
+        self.clear_num_leaves();
+        unsafe {
+            to_result(LGBM_BoosterShuffleModels(
+                self.handle,
+                start_iteration.try_into()?,
+                end_iteration.unwrap_or(0).try_into()?,
+            ))
+        }
+    }
+
     /// [LGBM_BoosterNumberOfTotalModel](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterNumberOfTotalModel)
     pub fn number_of_total_model(&self) -> Result<usize> {
         let mut value = 0;
@@ -337,6 +622,32 @@ impl Booster {
         Ok(value as usize)
     }
 
+    /// [LGBM_BoosterGetUpperBoundValue](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetUpperBoundValue)
+    ///
+    /// Returns the maximum raw score of the model.
+    /// Use [`Objective::convert_output`](crate::parameters::Objective::convert_output) to get the bound in the prediction space.
+    #[doc(alias = "LGBM_BoosterGetUpperBoundValue")]
+    pub fn upper_bound_value(&self) -> Result<f64> {
+        let mut value = 0.0;
+        unsafe {
+            to_result(LGBM_BoosterGetUpperBoundValue(self.handle, &mut value))?;
+        }
+        Ok(value)
+    }
+
+    /// [LGBM_BoosterGetLowerBoundValue](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetLowerBoundValue)
+    ///
+    /// Returns the minimum raw score of the model.
+    /// Use [`Objective::convert_output`](crate::parameters::Objective::convert_output) to get the bound in the prediction space.
+    #[doc(alias = "LGBM_BoosterGetLowerBoundValue")]
+    pub fn lower_bound_value(&self) -> Result<f64> {
+        let mut value = 0.0;
+        unsafe {
+            to_result(LGBM_BoosterGetLowerBoundValue(self.handle, &mut value))?;
+        }
+        Ok(value)
+    }
+
     /// [LGBM_BoosterGetPredict](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterGetPredict)
     #[doc(alias = "LGBM_BoosterGetPredict")]
     pub fn get_predict(&self, data_idx: usize) -> Result<Prediction> {
@@ -357,6 +668,22 @@ impl Booster {
         Ok(out_result)
     }
 
+    /// Returns the result of [`get_predict`](Self::get_predict) indexed by `[row, class]`.
+    ///
+    /// `get_predict` returns class-major values for multiclass models.
+    pub(crate) fn get_predict_by_row(&self, data_idx: usize) -> Result<Prediction> {
+        let mut preds = self.get_predict(data_idx)?;
+        let num_data = preds.num_data();
+        let num_class = preds.num_class();
+        if num_class > 1 {
+            let values = &preds.values;
+            preds.values = (0..num_data * num_class)
+                .map(|i| values[(i % num_class) * num_data + i / num_class])
+                .collect();
+        }
+        Ok(preds)
+    }
+
     /// [LGBM_BoosterPredictForMat](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForMat)
     #[doc(alias = "LGBM_BoosterPredictForMat")]
     pub fn predict_for_mat<T: FeatureData>(
@@ -368,13 +695,7 @@ impl Booster {
         parameters: &Parameters,
     ) -> Result<Prediction> {
         let mat = mat.as_mat();
-        let num_feature = self.get_num_feature()?;
-        if num_feature != mat.ncol() {
-            return Err(Error::from_message(&format!(
-                "column size must be {num_feature}, but got {}",
-                mat.ncol(),
-            )));
-        }
+        self.check_num_feature(mat.ncol())?;
         let num_row = mat.nrow();
         let num_class = self.get_num_classes()?;
         let num_predict =
@@ -403,9 +724,172 @@ impl Booster {
         Ok(out_result)
     }
 
+    /// [LGBM_BoosterPredictForCSR](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForCSR)
+    #[doc(alias = "LGBM_BoosterPredictForCSR")]
+    pub fn predict_for_csr<T: FeatureData, P: IndPtrData>(
+        &self,
+        csr: impl AsSparseMat<T, P, Layout = Csr>,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        parameters: &Parameters,
+    ) -> Result<Prediction> {
+        let csr = csr.as_sparse_mat();
+        self.check_num_feature(csr.ncol())?;
+        let num_row = csr.nrow();
+        let num_class = self.get_num_classes()?;
+        let num_predict =
+            self.calc_num_predict(num_row, predict_type, start_iteration, num_iteration)?;
+        let num_iteration = num_iteration.unwrap_or(0).try_into()?;
+
+        let mut out_result = Prediction::from_num_predict(num_predict, num_row, num_class)?;
+        let mut out_len = 0;
+        unsafe {
+            to_result(LGBM_BoosterPredictForCSR(
+                self.handle,
+                csr.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csr.indices().as_ptr(),
+                csr.as_data_ptr(),
+                T::DATA_TYPE,
+                csr.indptr().len().try_into()?,
+                csr.nnz().try_into()?,
+                csr.ncol().try_into()?,
+                predict_type.to_cint(),
+                start_iteration.try_into()?,
+                num_iteration,
+                parameters.to_cstring()?.as_ptr(),
+                &mut out_len,
+                out_result.values.as_mut_ptr(),
+            ))?;
+        }
+        assert_eq!(out_len as usize, out_result.values.len());
+        Ok(out_result)
+    }
+
+    /// [LGBM_BoosterPredictForCSC](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForCSC)
+    #[doc(alias = "LGBM_BoosterPredictForCSC")]
+    pub fn predict_for_csc<T: FeatureData, P: IndPtrData>(
+        &self,
+        csc: impl AsSparseMat<T, P, Layout = Csc>,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        parameters: &Parameters,
+    ) -> Result<Prediction> {
+        let csc = csc.as_sparse_mat();
+        self.check_num_feature(csc.ncol())?;
+        let num_row = csc.nrow();
+        let num_class = self.get_num_classes()?;
+        let num_predict =
+            self.calc_num_predict(num_row, predict_type, start_iteration, num_iteration)?;
+        let num_iteration = num_iteration.unwrap_or(0).try_into()?;
+
+        let mut out_result = Prediction::from_num_predict(num_predict, num_row, num_class)?;
+        let mut out_len = 0;
+        unsafe {
+            to_result(LGBM_BoosterPredictForCSC(
+                self.handle,
+                csc.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csc.indices().as_ptr(),
+                csc.as_data_ptr(),
+                T::DATA_TYPE,
+                csc.indptr().len().try_into()?,
+                csc.nnz().try_into()?,
+                csc.nrow().try_into()?,
+                predict_type.to_cint(),
+                start_iteration.try_into()?,
+                num_iteration,
+                parameters.to_cstring()?.as_ptr(),
+                &mut out_len,
+                out_result.values.as_mut_ptr(),
+            ))?;
+        }
+        assert_eq!(out_len as usize, out_result.values.len());
+        Ok(out_result)
+    }
+
+    /// [LGBM_BoosterPredictSparseOutput](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictSparseOutput)
+    ///
+    /// Computes feature contributions ([`PredictType::Contrib`]) in the same sparse format as the input.
+    #[doc(alias = "LGBM_BoosterPredictSparseOutput")]
+    pub fn predict_sparse_output<T: FeatureData, P: IndPtrData, L: SparseLayout>(
+        &self,
+        mat: impl AsSparseMat<T, P, Layout = L>,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        parameters: &Parameters,
+    ) -> Result<SparsePrediction<T, P, L>> {
+        let mat = mat.as_sparse_mat();
+        self.check_num_feature(mat.ncol())?;
+        let num_class = self.num_model_per_iteration()?;
+        let num_col_or_row = match mat.matrix_type() {
+            MatrixType::Csr => mat.ncol(),
+            MatrixType::Csc => mat.nrow(),
+        };
+        let nindptr = mat.indptr().len().try_into()?;
+        let nelem = mat.nnz().try_into()?;
+        let num_col_or_row = num_col_or_row.try_into()?;
+        let start_iteration = start_iteration.try_into()?;
+        let num_iteration = num_iteration.unwrap_or(0).try_into()?;
+        let parameters = parameters.to_cstring()?;
+
+        let mut out_len = [0i64; 2];
+        let mut out_indptr = null_mut();
+        let mut out_indices = null_mut();
+        let mut out_data = null_mut();
+        unsafe {
+            to_result(LGBM_BoosterPredictSparseOutput(
+                self.handle,
+                mat.as_indptr_ptr(),
+                P::DATA_TYPE,
+                mat.indices().as_ptr(),
+                mat.as_data_ptr(),
+                T::DATA_TYPE,
+                nindptr,
+                nelem,
+                num_col_or_row,
+                PredictType::Contrib.to_cint(),
+                start_iteration,
+                num_iteration,
+                parameters.as_ptr(),
+                mat.matrix_type().to_cint(),
+                out_len.as_mut_ptr(),
+                &mut out_indptr,
+                &mut out_indices,
+                &mut out_data,
+            ))?;
+        }
+        let out = SparsePrediction {
+            indptr: out_indptr as *mut P,
+            indices: out_indices,
+            values: out_data as *mut T,
+            nnz: out_len[0] as usize,
+            indptr_len: out_len[1] as usize,
+            offsets: Vec::new(),
+            num_data: mat.nrow(),
+            num_class,
+            num_feature: mat.ncol(),
+            layout: mat.layout(),
+        };
+        out.with_offsets()
+    }
+
+    fn check_num_feature(&self, ncol: usize) -> Result<()> {
+        let num_feature = self.get_num_feature()?;
+        if num_feature != ncol {
+            return Err(Error::from_message(&format!(
+                "column size must be {num_feature}, but got {ncol}",
+            )));
+        }
+        Ok(())
+    }
+
     /// [LGBM_BoosterUpdateOneIter](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterUpdateOneIter)
     #[doc(alias = "LGBM_BoosterUpdateOneIter")]
     pub fn update_one_iter(&mut self) -> Result<bool> {
+        self.clear_num_leaves();
         let mut is_finished = 0;
         unsafe {
             to_result(LGBM_BoosterUpdateOneIter(self.handle, &mut is_finished))?;
@@ -414,12 +898,28 @@ impl Booster {
     }
 
     /// [LGBM_BoosterUpdateOneIterCustom](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterUpdateOneIterCustom)
+    ///
+    /// `grad` and `hess` have `num_class * num_data` elements in class-major order.
+    /// The value for `row` of `class` is at `class * num_data + row`.
     #[doc(alias = "LGBM_BoosterUpdateOneIterCustom")]
     pub fn update_one_iter_custom(&mut self, grad: &[f32], hess: &[f32]) -> Result<bool> {
         let num_class = self.get_num_classes()?;
         let num_data = self.get_num_data(0)?;
-        assert_eq!(grad.len(), num_class * num_data, "mismatch grad length.");
-        assert_eq!(hess.len(), num_class * num_data, "mismatch hess length.");
+        if grad.len() != num_class * num_data {
+            return Err(Error::from_message(&format!(
+                "grad length must be {}, but got {}",
+                num_class * num_data,
+                grad.len()
+            )));
+        }
+        if hess.len() != num_class * num_data {
+            return Err(Error::from_message(&format!(
+                "hess length must be {}, but got {}",
+                num_class * num_data,
+                hess.len()
+            )));
+        }
+        self.clear_num_leaves();
         let mut is_finished = 0;
         unsafe {
             to_result(LGBM_BoosterUpdateOneIterCustom(
@@ -432,9 +932,103 @@ impl Booster {
         Ok(int_to_bool(is_finished))
     }
 
+    /// Update the model for one iteration using gradients and hessians computed by `objective`.
+    ///
+    /// The `objective` parameter should be [`Custom`](crate::parameters::Objective::Custom)
+    /// so that [`get_predict`](Self::get_predict) returns raw scores.
+    pub fn update_one_iter_objective(&mut self, objective: &dyn Objective) -> Result<bool> {
+        let Some(Some(train)) = self.data.first() else {
+            return Err(Error::from_message("training data is not available"));
+        };
+        let train = train.clone();
+        let preds = self.get_predict_by_row(0)?;
+        let len = preds.num_data() * preds.num_class();
+        let mut grad = vec![0.0; len];
+        let mut hess = vec![0.0; len];
+        objective.grad_hess(&preds, &train, &mut grad, &mut hess);
+        self.update_one_iter_custom(&grad, &hess)
+    }
+
+    /// [LGBM_BoosterResetParameter](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterResetParameter)
+    ///
+    /// Parameters such as `learning_rate` and `bagging_fraction` can be changed during training.
+    /// Changing `num_class`, `boosting` or `metric` returns an error.
+    #[doc(alias = "LGBM_BoosterResetParameter")]
+    pub fn reset_parameter(&mut self, parameters: &Parameters) -> Result<()> {
+        unsafe {
+            to_result(LGBM_BoosterResetParameter(
+                self.handle,
+                parameters.to_cstring()?.as_ptr(),
+            ))
+        }
+    }
+
+    /// [LGBM_BoosterRefit](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterRefit)
+    ///
+    /// Refit the leaf values of the existing trees using the training dataset.
+    /// `leaf_preds` has `num_data` rows and [`number_of_total_model`](Self::number_of_total_model) columns
+    /// and contains the leaf indices predicted by [`PredictType::LeafIndex`].
+    #[doc(alias = "LGBM_BoosterRefit")]
+    pub fn refit(&mut self, leaf_preds: &MatBuf<i32, RowMajor>) -> Result<()> {
+        let num_data = self.get_num_data(0)?;
+        let num_total_model = self.number_of_total_model()?;
+        if leaf_preds.nrow() != num_data {
+            return Err(Error::from_message(&format!(
+                "row size must be {num_data}, but got {}",
+                leaf_preds.nrow()
+            )));
+        }
+        if leaf_preds.ncol() != num_total_model {
+            return Err(Error::from_message(&format!(
+                "column size must be {num_total_model}, but got {}",
+                leaf_preds.ncol()
+            )));
+        }
+        unsafe {
+            to_result(LGBM_BoosterRefit(
+                self.handle,
+                leaf_preds.as_slice().as_ptr(),
+                leaf_preds.nrow().try_into()?,
+                leaf_preds.ncol().try_into()?,
+            ))
+        }
+    }
+
+    /// Refit the leaf values of the existing trees on new data.
+    ///
+    /// The leaf indices of `mat` are computed by [`PredictType::LeafIndex`],
+    /// then the model is replaced by a booster trained on `mat` and `label` with `parameters`.
+    /// `parameters` should contain the parameters used to train the model, such as `objective`.
+    pub fn refit_mat<T: FeatureData>(
+        &mut self,
+        mat: impl AsMat<T>,
+        label: &[f32],
+        parameters: &Parameters,
+    ) -> Result<()> {
+        let mat = mat.as_mat();
+        let leaf_preds = self.predict_for_mat(mat, PredictType::LeafIndex, 0, None, parameters)?;
+        let num_total_model = self.number_of_total_model()?;
+        let leaf_preds = MatBuf::from_vec(
+            leaf_preds.values().iter().map(|&x| x as i32).collect(),
+            mat.nrow(),
+            num_total_model,
+            RowMajor,
+        );
+        let mut train = Dataset::from_mat(mat, None, parameters)?;
+        train.set_field(Field::LABEL, label)?;
+        let mut booster = Booster::new(Arc::new(train), parameters)?;
+        unsafe {
+            to_result(LGBM_BoosterMerge(booster.handle, self.handle))?;
+        }
+        booster.refit(&leaf_preds)?;
+        *self = booster;
+        Ok(())
+    }
+
     /// [LGBM_BoosterRollbackOneIter](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterRollbackOneIter)
     #[doc(alias = "LGBM_BoosterRollbackOneIter")]
     pub fn rollback_one_iter(&mut self) -> Result<()> {
+        self.clear_num_leaves();
         unsafe { to_result(LGBM_BoosterRollbackOneIter(self.handle)) }
     }
 
@@ -493,166 +1087,137 @@ impl Drop for Booster {
 unsafe impl Send for Booster {}
 unsafe impl Sync for Booster {}
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Prediction {
-    num: [usize; 3],
-    values: Vec<f64>,
+/// Sparse feature contributions returned by [`Booster::predict_sparse_output`].
+///
+/// Contains one sparse matrix per class, each with `num_data` rows and `num_feature + 1` columns.
+/// The last column is the expected value.
+pub struct SparsePrediction<T: FeatureData, P: IndPtrData, L: SparseLayout> {
+    indptr: *mut P,
+    indices: *mut i32,
+    values: *mut T,
+    nnz: usize,
+    indptr_len: usize,
+    offsets: Vec<usize>,
+    num_data: usize,
+    num_class: usize,
+    num_feature: usize,
+    layout: L,
 }
-impl Prediction {
-    fn new(num_data: usize, num_class: usize, num_2: usize) -> Self {
-        Self {
-            values: vec![f64::NAN; num_data * num_class * num_2],
-            num: [num_data, num_class, num_2],
+impl<T: FeatureData, P: IndPtrData, L: SparseLayout> SparsePrediction<T, P, L> {
+    fn with_offsets(mut self) -> Result<Self> {
+        let per_class_len = self.per_class_indptr_len();
+        if self.indptr_len != per_class_len * self.num_class {
+            return Err(Error::from_message("invalid indptr length"));
         }
-    }
-    fn from_num_predict(num_predict: usize, num_data: usize, num_class: usize) -> Result<Self> {
-        if num_data * num_class == 0 {
-            return Ok(Self::new(num_data, num_class, 1));
+        let indptr = self.indptr();
+        let mut offset = 0;
+        let mut offsets = vec![0];
+        for class in 0..self.num_class {
+            let last = indptr[(class + 1) * per_class_len - 1];
+            offset += last
+                .to_usize()
+                .ok_or_else(|| Error::from_message("invalid indptr"))?;
+            offsets.push(offset);
         }
-        let num_2 = num_predict / num_class / num_data;
-        if num_data * num_class * num_2 != num_predict {
-            return Err(Error::from_message("invalid num_data"));
+        if offset != self.nnz {
+            return Err(Error::from_message("invalid number of elements"));
         }
-        Ok(Self::new(num_data, num_class, num_2))
+        self.offsets = offsets;
+        Ok(self)
     }
-
-    pub fn values(&self) -> &[f64] {
-        &self.values
+    fn per_class_indptr_len(&self) -> usize {
+        match self.layout.matrix_type() {
+            MatrixType::Csr => self.num_data + 1,
+            MatrixType::Csc => self.num_feature + 2,
+        }
+    }
+    fn indptr(&self) -> &[P] {
+        to_slice(self.indptr, self.indptr_len)
+    }
+    fn indices(&self) -> &[i32] {
+        to_slice(self.indices, self.nnz)
+    }
+    fn values(&self) -> &[T] {
+        to_slice(self.values, self.nnz)
     }
+
     pub fn num_data(&self) -> usize {
-        self.num[0]
+        self.num_data
     }
     pub fn num_class(&self) -> usize {
-        self.num[1]
-    }
-    pub fn num_iteration(&self) -> usize {
-        self.num[2]
+        self.num_class
     }
     pub fn num_feature(&self) -> usize {
-        self.num[2] - 1
-    }
-
-    fn fmt_with<T: Cells>(
-        &self,
-        f: &mut std::fmt::Formatter<'_>,
-        to_cells: impl Fn(f64) -> T,
-    ) -> std::fmt::Result {
-        writeln!(f, "num_data  : {}", self.num_data())?;
-        writeln!(f, "num_class : {}", self.num_class())?;
-        writeln!(f, "num_2     : {}", self.num[2])?;
-        self.fmt_values_with(f, to_cells)
-    }
-    fn fmt_values_with<T: Cells>(
-        &self,
-        f: &mut std::fmt::Formatter<'_>,
-        to_cells: impl Fn(f64) -> T,
-    ) -> std::fmt::Result {
-        writeln!(f)?;
-        match (self.num_class(), self.num[2]) {
-            (_, 1) => {
-                let schema = cells_schema(|f| {
-                    f.column("", |&row| row);
-                    for column in 0..self.num_class() {
-                        f.column(column, |&row| to_cells(self[[row, column]]));
-                    }
-                });
-                writeln!(f, "{}", to_grid_with_schema(0..self.num_data(), schema))?;
-            }
-            (_, _) => {
-                writeln!(f, "{:?}", &self.values)?;
-            }
-        }
-        Ok(())
+        self.num_feature
     }
 
-    pub fn approx_eq(&self, other: &Self, margin: f64) -> bool {
-        self.values.len() == other.values.len()
-            && self
-                .values
-                .iter()
-                .zip(other.values.iter())
-                .all(|(&v0, &v1)| approx_eq(v0, v1, margin))
+    /// Returns the contributions for `class` as a sparse matrix with `num_feature + 1` columns.
+    #[track_caller]
+    pub fn class(&self, class: usize) -> SparseMat<'_, T, P, L> {
+        assert!(
+            class < self.num_class,
+            "index out of bounds: the num_class is {} but the class is {class}",
+            self.num_class
+        );
+        let per_class_len = self.per_class_indptr_len();
+        let range = self.offsets[class]..self.offsets[class + 1];
+        SparseMat::from_slices_unchecked(
+            &self.indptr()[class * per_class_len..][..per_class_len],
+            &self.indices()[range.clone()],
+            &self.values()[range],
+            self.num_data,
+            self.num_feature + 1,
+            self.layout,
+        )
     }
-}
-fn approx_eq(v0: f64, v1: f64, margin: f64) -> bool {
-    // `v0 == v1` to return true for the same infinity.
-    v0 == v1 || (v0 - v1).abs() <= margin
-}
 
-impl std::fmt::Display for Prediction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let p = f.precision();
-        if let Some(p) = p {
-            self.fmt_with(f, |x| cells_f!("{:.*}", p, x))
-        } else {
-            self.fmt_with(f, |x| cells_f!("{:}", x))
-        }
-    }
-}
-impl std::fmt::LowerExp for Prediction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let p = f.precision();
-        if let Some(p) = p {
-            self.fmt_with(f, |x| cells_f!("{:.*e}", p, x))
-        } else {
-            self.fmt_with(f, |x| cells_f!("{:e}", x))
-        }
-    }
-}
-impl std::fmt::UpperExp for Prediction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let p = f.precision();
-        if let Some(p) = p {
-            self.fmt_with(f, |x| cells_f!("{:.*E}", p, x))
-        } else {
-            self.fmt_with(f, |x| cells_f!("{:E}", x))
-        }
-    }
-}
-impl std::fmt::Debug for Prediction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        struct Values<'a>(&'a Prediction);
-        impl std::fmt::Debug for Values<'_> {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                let p = f.precision();
-                if let Some(p) = p {
-                    self.0.fmt_values_with(f, |x| cells_f!("{:.*?}", p, x))
-                } else {
-                    self.0.fmt_values_with(f, |x| cells_f!("{:?}", x))
-                }
+    /// Convert to a dense [`Prediction`].
+    pub fn to_prediction(&self) -> Prediction
+    where
+        T: Copy + Into<f64>,
+    {
+        let num_2 = self.num_feature + 1;
+        let mut p = Prediction {
+            num: [self.num_data, self.num_class, num_2],
+            values: vec![0.0; self.num_data * self.num_class * num_2],
+        };
+        for class in 0..self.num_class {
+            for (data, feature, &value) in self.class(class).iter() {
+                p.values[data * self.num_class * num_2 + class * num_2 + feature] = value.into();
             }
         }
-        f.debug_struct("Prediction")
-            .field("num", &self.num)
-            .field("values", &Values(self))
-            .finish()
-    }
-}
-
-impl std::ops::Index<usize> for Prediction {
-    type Output = f64;
-    fn index(&self, data: usize) -> &f64 {
-        assert_eq!(self.num_class(), 1, "num_class");
-        assert_eq!(self.num[2], 1, "num_2");
-        &self.values[data]
+        p
     }
 }
-impl std::ops::Index<[usize; 2]> for Prediction {
-    type Output = f64;
-    fn index(&self, [data, class]: [usize; 2]) -> &f64 {
-        assert_eq!(self.num[2], 1, "num_2");
-        &self.values[data * self.num_class() + class]
+impl<T: FeatureData, P: IndPtrData> SparsePrediction<T, P, Csr> {
+    /// Returns an iterator over the rows of `class` as `(feature_indices, values)`.
+    #[track_caller]
+    pub fn rows(&self, class: usize) -> impl Iterator<Item = (&[i32], &[T])> {
+        let mat = self.class(class);
+        (0..self.num_data).map(move |row| mat.row(row))
     }
 }
-impl std::ops::Index<[usize; 3]> for Prediction {
-    type Output = f64;
-    fn index(&self, [data, class, iteration]: [usize; 3]) -> &f64 {
-        &self.values[data * self.num_class() * self.num[2] + class * self.num[2] + iteration]
+impl<T: FeatureData, P: IndPtrData, L: SparseLayout> Drop for SparsePrediction<T, P, L> {
+    fn drop(&mut self) {
+        unsafe {
+            to_result(LGBM_BoosterFreePredictSparse(
+                self.indptr as *mut c_void,
+                self.indices,
+                self.values as *mut c_void,
+                P::DATA_TYPE,
+                T::DATA_TYPE,
+            ))
+            .unwrap();
+        }
     }
 }
+unsafe impl<T: FeatureData, P: IndPtrData, L: SparseLayout> Send for SparsePrediction<T, P, L> {}
+unsafe impl<T: FeatureData, P: IndPtrData, L: SparseLayout> Sync for SparsePrediction<T, P, L> {}
 
-impl From<Prediction> for Vec<f64> {
-    fn from(p: Prediction) -> Self {
-        p.values
+fn to_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if ptr.is_null() {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(ptr, len) }
     }
 }
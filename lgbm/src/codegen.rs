@@ -0,0 +1,548 @@
+//! Generate standalone source code from a model
+//!
+//! The generated code has no dependencies and evaluates the trees with nested `if`/`else`.
+//!
+//! ```ignore
+//! let model = booster.dump_model_typed(0, None, FeatureImportanceType::Split)?;
+//! let code = lgbm::codegen::generate(&model, Language::Rust)?;
+//! ```
+//!
+//! The generated Rust module contains the following items.
+//!
+//! ```ignore
+//! pub const NUM_FEATURE: usize = ..;
+//! pub const NUM_CLASS: usize = ..;
+//! pub fn predict(features: &[f64]) -> [f64; NUM_CLASS];
+//! pub fn predict_raw(features: &[f64]) -> [f64; NUM_CLASS];
+//! ```
+//!
+//! The generated C file contains the following items.
+//!
+//! ```c
+//! #define LGBM_NUM_FEATURE ..
+//! #define LGBM_NUM_CLASS ..
+//! void lgbm_predict(const double *features, double *out);
+//! void lgbm_predict_raw(const double *features, double *out);
+//! ```
+//!
+//! `predict` corresponds to [`PredictType::Normal`](crate::PredictType::Normal)
+//! and `predict_raw` corresponds to [`PredictType::RawScore`](crate::PredictType::RawScore).
+
+use crate::{
+    Error, Result,
+    convert_output::ConvertOutput,
+    model::{LeafNode, MissingType, ModelDump, Node, SplitNode, Threshold, ZERO_THRESHOLD},
+};
+use std::fmt::Write;
+
+/// Language of the generated code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language {
+    Rust,
+    C,
+}
+
+/// Generate source code that predicts with `model`.
+pub fn generate(model: &ModelDump, language: Language) -> Result<String> {
+    let num_tree_per_iteration = model.num_tree_per_iteration;
+    if num_tree_per_iteration == 0 || !model.tree_info.len().is_multiple_of(num_tree_per_iteration)
+    {
+        return Err(Error::from_message(&format!(
+            "number of trees must be a multiple of {num_tree_per_iteration}, but got {}",
+            model.tree_info.len()
+        )));
+    }
+    let convert_output = match &model.objective {
+        Some(objective) => ConvertOutput::parse(objective)?,
+        None => ConvertOutput::Identity,
+    };
+    let mut g = Generator {
+        language,
+        num_feature: model.max_feature_idx + 1,
+        num_class: num_tree_per_iteration,
+        s: String::new(),
+        bitsets: Vec::new(),
+    };
+    let mut trees = String::new();
+    for (index, tree) in model.tree_info.iter().enumerate() {
+        g.tree(&mut trees, index, &tree.tree_structure)?;
+    }
+    g.header();
+    g.bitsets();
+    g.s.push_str(&trees);
+    g.predict_raw(model);
+    g.predict(convert_output);
+    Ok(g.s)
+}
+
+/// Generate source code that predicts with the model of `booster`.
+#[cfg(feature = "lightgbm")]
+pub fn generate_from_booster(booster: &crate::Booster, language: Language) -> Result<String> {
+    let model = booster.dump_model_typed(0, None, crate::FeatureImportanceType::Split)?;
+    generate(&model, language)
+}
+
+/// Generate source code that predicts with a model string.
+///
+/// `model` is either the JSON returned by `Booster::dump_model`
+/// or a text model such as the one returned by `Booster::save_model_to_string`.
+/// Text models require the `lightgbm` feature.
+pub fn generate_from_model_string(model: &str, language: Language) -> Result<String> {
+    if model.trim_start().starts_with('{') {
+        return generate(&serde_json::from_str(model)?, language);
+    }
+    #[cfg(feature = "lightgbm")]
+    {
+        let (booster, _) = crate::Booster::from_string(&std::ffi::CString::new(model)?)?;
+        generate_from_booster(&booster, language)
+    }
+    #[cfg(not(feature = "lightgbm"))]
+    Err(Error::from_message(
+        "text models require the `lightgbm` feature",
+    ))
+}
+
+struct Generator {
+    language: Language,
+    num_feature: usize,
+    num_class: usize,
+    s: String,
+    bitsets: Vec<Vec<u32>>,
+}
+
+impl Generator {
+    fn header(&mut self) {
+        let s = &mut self.s;
+        let num_feature = self.num_feature;
+        let num_class = self.num_class;
+        match self.language {
+            Language::Rust => {
+                writeln!(s, "// Generated by lgbm. Do not edit.").unwrap();
+                writeln!(s).unwrap();
+                writeln!(s, "pub const NUM_FEATURE: usize = {num_feature};").unwrap();
+                writeln!(s, "pub const NUM_CLASS: usize = {num_class};").unwrap();
+                if self.bitsets.is_empty() {
+                    return;
+                }
+                writeln!(s).unwrap();
+                writeln!(s, "fn in_bitset(value: f64, bitset: &[u32]) -> bool {{").unwrap();
+                writeln!(
+                    s,
+                    "    if value.is_nan() || value <= -1.0 || value >= (bitset.len() * 32) as f64 {{"
+                )
+                .unwrap();
+                writeln!(s, "        return false;").unwrap();
+                writeln!(s, "    }}").unwrap();
+                writeln!(s, "    let i = value as usize;").unwrap();
+                writeln!(s, "    (bitset[i / 32] >> (i % 32)) & 1 != 0").unwrap();
+                writeln!(s, "}}").unwrap();
+            }
+            Language::C => {
+                writeln!(s, "/* Generated by lgbm. Do not edit. */").unwrap();
+                writeln!(s).unwrap();
+                writeln!(s, "#include <math.h>").unwrap();
+                writeln!(s, "#include <stdint.h>").unwrap();
+                writeln!(s).unwrap();
+                writeln!(s, "#define LGBM_NUM_FEATURE {num_feature}").unwrap();
+                writeln!(s, "#define LGBM_NUM_CLASS {num_class}").unwrap();
+                if self.bitsets.is_empty() {
+                    return;
+                }
+                writeln!(s).unwrap();
+                writeln!(
+                    s,
+                    "static int lgbm_in_bitset(double value, const uint32_t *bitset, int len) {{"
+                )
+                .unwrap();
+                writeln!(
+                    s,
+                    "    if (isnan(value) || value <= -1.0 || value >= len * 32.0) {{"
+                )
+                .unwrap();
+                writeln!(s, "        return 0;").unwrap();
+                writeln!(s, "    }}").unwrap();
+                writeln!(s, "    int i = (int)value;").unwrap();
+                writeln!(s, "    return (bitset[i / 32] >> (i % 32)) & 1;").unwrap();
+                writeln!(s, "}}").unwrap();
+            }
+        }
+    }
+    fn bitsets(&mut self) {
+        let s = &mut self.s;
+        for (index, bitset) in self.bitsets.iter().enumerate() {
+            let values = bitset
+                .iter()
+                .map(|b| b.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let len = bitset.len();
+            writeln!(s).unwrap();
+            match self.language {
+                Language::Rust => {
+                    writeln!(s, "const BITSET_{index}: [u32; {len}] = [{values}];").unwrap()
+                }
+                Language::C => writeln!(
+                    s,
+                    "static const uint32_t lgbm_bitset_{index}[{len}] = {{{values}}};"
+                )
+                .unwrap(),
+            }
+        }
+    }
+    fn tree(&mut self, s: &mut String, index: usize, root: &Node) -> Result<()> {
+        writeln!(s).unwrap();
+        let f = match root {
+            Node::Leaf(leaf) if leaf.leaf_features.is_empty() => "_f",
+            _ => "f",
+        };
+        match self.language {
+            Language::Rust => writeln!(s, "fn tree_{index}({f}: &[f64; NUM_FEATURE]) -> f64 {{"),
+            Language::C => writeln!(s, "static double lgbm_tree_{index}(const double *f) {{"),
+        }
+        .unwrap();
+        if self.language == Language::C && f == "_f" {
+            writeln!(s, "    (void)f;").unwrap();
+        }
+        match self.language {
+            Language::Rust => self.node_expr(s, root, 1)?,
+            Language::C => self.node_stmt(s, root, 1)?,
+        }
+        writeln!(s, "}}").unwrap();
+        Ok(())
+    }
+
+    /// Write `node` as a Rust expression.
+    fn node_expr(&mut self, s: &mut String, node: &Node, depth: usize) -> Result<()> {
+        let indent = "    ".repeat(depth);
+        match node {
+            Node::Split(split) => {
+                let cond = self.condition(split)?;
+                writeln!(s, "{indent}if {cond} {{").unwrap();
+                self.node_expr(s, &split.left_child, depth + 1)?;
+                writeln!(s, "{indent}}} else {{").unwrap();
+                self.node_expr(s, &split.right_child, depth + 1)?;
+                writeln!(s, "{indent}}}").unwrap();
+            }
+            Node::Leaf(leaf) => writeln!(s, "{indent}{}", self.leaf(leaf)?).unwrap(),
+        }
+        Ok(())
+    }
+
+    /// Write `node` as C statements that return the leaf value.
+    fn node_stmt(&mut self, s: &mut String, node: &Node, depth: usize) -> Result<()> {
+        let indent = "    ".repeat(depth);
+        match node {
+            Node::Split(split) => {
+                let cond = self.condition(split)?;
+                writeln!(s, "{indent}if ({cond}) {{").unwrap();
+                self.node_stmt(s, &split.left_child, depth + 1)?;
+                writeln!(s, "{indent}}} else {{").unwrap();
+                self.node_stmt(s, &split.right_child, depth + 1)?;
+                writeln!(s, "{indent}}}").unwrap();
+            }
+            Node::Leaf(leaf) => writeln!(s, "{indent}return {};", self.leaf(leaf)?).unwrap(),
+        }
+        Ok(())
+    }
+
+    /// Expression that is true if a row goes to the left child of `split`.
+    fn condition(&mut self, split: &SplitNode) -> Result<String> {
+        let f = self.feature(split.split_feature)?;
+        let is_nan = self.is_nan(&f);
+        Ok(match &split.threshold {
+            Threshold::Numerical(threshold) => {
+                let t = literal(self.language, *threshold);
+                match (split.missing_type, split.default_left) {
+                    // NaN is treated as 0.0.
+                    (MissingType::None, _) if 0.0 <= *threshold => format!("!({f} > {t})"),
+                    (MissingType::None, _) => format!("{f} <= {t}"),
+                    // NaN is treated as 0.0, and 0.0 is missing.
+                    (MissingType::Zero, true) => format!("{is_nan} || {f} == 0.0 || {f} <= {t}"),
+                    (MissingType::Zero, false) => format!("{f} != 0.0 && {f} <= {t}"),
+                    (MissingType::NaN, true) => format!("!({f} > {t})"),
+                    (MissingType::NaN, false) => format!("{f} <= {t}"),
+                }
+            }
+            Threshold::Categorical(categories) => {
+                let mut bitset = Vec::new();
+                for &category in categories {
+                    let category = usize::try_from(category).map_err(|_| {
+                        Error::from_message(&format!("invalid category: {category}"))
+                    })?;
+                    if bitset.len() <= category / 32 {
+                        bitset.resize(category / 32 + 1, 0u32);
+                    }
+                    bitset[category / 32] |= 1 << (category % 32);
+                }
+                let index = self.bitsets.len();
+                let len = bitset.len();
+                self.bitsets.push(bitset);
+                match self.language {
+                    Language::Rust => format!("in_bitset({f}, &BITSET_{index})"),
+                    Language::C => format!("lgbm_in_bitset({f}, lgbm_bitset_{index}, {len})"),
+                }
+            }
+        })
+    }
+    fn leaf(&self, leaf: &LeafNode) -> Result<String> {
+        let value = literal(self.language, leaf.leaf_value);
+        let Some(leaf_const) = leaf.leaf_const else {
+            return Ok(value);
+        };
+        if leaf.leaf_features.len() != leaf.leaf_coeff.len() {
+            return Err(Error::from_message(
+                "leaf_features and leaf_coeff must have the same length",
+            ));
+        }
+        let mut nan = Vec::new();
+        let mut linear = literal(self.language, leaf_const);
+        for (&feature, &coeff) in leaf.leaf_features.iter().zip(&leaf.leaf_coeff) {
+            let f = self.feature(feature)?;
+            nan.push(self.is_nan(&f));
+            write!(linear, " + {} * {f}", literal(self.language, coeff)).unwrap();
+        }
+        if nan.is_empty() {
+            return Ok(linear);
+        }
+        let nan = nan.join(" || ");
+        Ok(match self.language {
+            Language::Rust => format!("if {nan} {{ {value} }} else {{ {linear} }}"),
+            Language::C => format!("({nan}) ? {value} : {linear}"),
+        })
+    }
+    fn feature(&self, feature: usize) -> Result<String> {
+        if feature >= self.num_feature {
+            return Err(Error::from_message(&format!(
+                "feature index must be less than {}, but got {feature}",
+                self.num_feature
+            )));
+        }
+        Ok(format!("f[{feature}]"))
+    }
+    fn is_nan(&self, value: &str) -> String {
+        match self.language {
+            Language::Rust => format!("{value}.is_nan()"),
+            Language::C => format!("isnan({value})"),
+        }
+    }
+    fn predict_raw(&mut self, model: &ModelDump) {
+        let s = &mut self.s;
+        let zero_threshold = format!("{ZERO_THRESHOLD:?}");
+        let num_iteration = model.tree_info.len() / self.num_class;
+        writeln!(s).unwrap();
+        match self.language {
+            Language::Rust => {
+                writeln!(
+                    s,
+                    "/// Predict raw scores. `features` must have `NUM_FEATURE` elements."
+                )
+                .unwrap();
+                writeln!(
+                    s,
+                    "pub fn predict_raw(features: &[f64]) -> [f64; NUM_CLASS] {{"
+                )
+                .unwrap();
+                writeln!(s, "    assert_eq!(features.len(), NUM_FEATURE);").unwrap();
+                writeln!(s, "    let mut f = [0.0; NUM_FEATURE];").unwrap();
+                writeln!(s, "    for (f, &x) in f.iter_mut().zip(features) {{").unwrap();
+                writeln!(
+                    s,
+                    "        *f = if x.abs() > {zero_threshold} || x.is_nan() {{ x }} else {{ 0.0 }};"
+                )
+                .unwrap();
+                writeln!(s, "    }}").unwrap();
+                writeln!(s, "    let mut out = [0.0; NUM_CLASS];").unwrap();
+                for (index, _) in model.tree_info.iter().enumerate() {
+                    let k = index % self.num_class;
+                    writeln!(s, "    out[{k}] += tree_{index}(&f);").unwrap();
+                }
+                if model.average_output && num_iteration > 0 {
+                    writeln!(s, "    for out in &mut out {{").unwrap();
+                    writeln!(s, "        *out /= {num_iteration}.0;").unwrap();
+                    writeln!(s, "    }}").unwrap();
+                }
+                writeln!(s, "    out").unwrap();
+                writeln!(s, "}}").unwrap();
+            }
+            Language::C => {
+                writeln!(
+                    s,
+                    "/* Predict raw scores. `features` must have LGBM_NUM_FEATURE elements and `out` must have LGBM_NUM_CLASS elements. */"
+                )
+                .unwrap();
+                writeln!(
+                    s,
+                    "void lgbm_predict_raw(const double *features, double *out) {{"
+                )
+                .unwrap();
+                writeln!(s, "    double f[LGBM_NUM_FEATURE];").unwrap();
+                writeln!(s, "    for (int i = 0; i < LGBM_NUM_FEATURE; i++) {{").unwrap();
+                writeln!(s, "        double x = features[i];").unwrap();
+                writeln!(
+                    s,
+                    "        f[i] = (fabs(x) > {zero_threshold} || isnan(x)) ? x : 0.0;"
+                )
+                .unwrap();
+                writeln!(s, "    }}").unwrap();
+                writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                writeln!(s, "        out[k] = 0.0;").unwrap();
+                writeln!(s, "    }}").unwrap();
+                for (index, _) in model.tree_info.iter().enumerate() {
+                    let k = index % self.num_class;
+                    writeln!(s, "    out[{k}] += lgbm_tree_{index}(f);").unwrap();
+                }
+                if model.average_output && num_iteration > 0 {
+                    writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                    writeln!(s, "        out[k] /= {num_iteration}.0;").unwrap();
+                    writeln!(s, "    }}").unwrap();
+                }
+                writeln!(s, "}}").unwrap();
+            }
+        }
+    }
+    fn predict(&mut self, convert_output: ConvertOutput) {
+        let s = &mut self.s;
+        writeln!(s).unwrap();
+        match self.language {
+            Language::Rust => {
+                writeln!(
+                    s,
+                    "/// Predict values. `features` must have `NUM_FEATURE` elements."
+                )
+                .unwrap();
+                writeln!(s, "pub fn predict(features: &[f64]) -> [f64; NUM_CLASS] {{").unwrap();
+                writeln!(s, "    #[allow(unused_mut)]").unwrap();
+                writeln!(s, "    let mut out = predict_raw(features);").unwrap();
+                match convert_output {
+                    ConvertOutput::Identity => {}
+                    ConvertOutput::Sqrt => {
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(
+                            s,
+                            "        let sign = if *x > 0.0 {{ 1.0 }} else if *x < 0.0 {{ -1.0 }} else {{ 0.0 }};"
+                        )
+                        .unwrap();
+                        writeln!(s, "        *x = sign * *x * *x;").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Exp => {
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(s, "        *x = x.exp();").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Sigmoid(sigmoid) => {
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(
+                            s,
+                            "        *x = 1.0 / (1.0 + ({} * *x).exp());",
+                            literal(Language::Rust, -sigmoid)
+                        )
+                        .unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Softmax => {
+                        writeln!(
+                            s,
+                            "    let max = out.iter().copied().fold(out[0], f64::max);"
+                        )
+                        .unwrap();
+                        writeln!(s, "    let mut sum = 0.0;").unwrap();
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(s, "        *x = (*x - max).exp();").unwrap();
+                        writeln!(s, "        sum += *x;").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(s, "        *x /= sum;").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::LogExp => {
+                        writeln!(s, "    for x in &mut out {{").unwrap();
+                        writeln!(s, "        let y = 1.0 + x.exp();").unwrap();
+                        writeln!(
+                            s,
+                            "        *x = if y > 0.0 {{ y.ln() }} else {{ f64::NEG_INFINITY }};"
+                        )
+                        .unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                }
+                writeln!(s, "    out").unwrap();
+                writeln!(s, "}}").unwrap();
+            }
+            Language::C => {
+                writeln!(
+                    s,
+                    "/* Predict values. `features` must have LGBM_NUM_FEATURE elements and `out` must have LGBM_NUM_CLASS elements. */"
+                )
+                .unwrap();
+                writeln!(
+                    s,
+                    "void lgbm_predict(const double *features, double *out) {{"
+                )
+                .unwrap();
+                writeln!(s, "    lgbm_predict_raw(features, out);").unwrap();
+                match convert_output {
+                    ConvertOutput::Identity => {}
+                    ConvertOutput::Sqrt => {
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(
+                            s,
+                            "        double sign = out[k] > 0.0 ? 1.0 : out[k] < 0.0 ? -1.0 : 0.0;"
+                        )
+                        .unwrap();
+                        writeln!(s, "        out[k] = sign * out[k] * out[k];").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Exp => {
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(s, "        out[k] = exp(out[k]);").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Sigmoid(sigmoid) => {
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(
+                            s,
+                            "        out[k] = 1.0 / (1.0 + exp({} * out[k]));",
+                            literal(Language::C, -sigmoid)
+                        )
+                        .unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::Softmax => {
+                        writeln!(s, "    double max = out[0];").unwrap();
+                        writeln!(s, "    for (int k = 1; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(s, "        max = fmax(max, out[k]);").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                        writeln!(s, "    double sum = 0.0;").unwrap();
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(s, "        out[k] = exp(out[k] - max);").unwrap();
+                        writeln!(s, "        sum += out[k];").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(s, "        out[k] /= sum;").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                    ConvertOutput::LogExp => {
+                        writeln!(s, "    for (int k = 0; k < LGBM_NUM_CLASS; k++) {{").unwrap();
+                        writeln!(s, "        double y = 1.0 + exp(out[k]);").unwrap();
+                        writeln!(s, "        out[k] = y > 0.0 ? log(y) : -INFINITY;").unwrap();
+                        writeln!(s, "    }}").unwrap();
+                    }
+                }
+                writeln!(s, "}}").unwrap();
+            }
+        }
+    }
+}
+
+fn literal(language: Language, value: f64) -> String {
+    match (language, value) {
+        (Language::Rust, v) if v.is_nan() => "f64::NAN".to_string(),
+        (Language::Rust, f64::INFINITY) => "f64::INFINITY".to_string(),
+        (Language::Rust, f64::NEG_INFINITY) => "f64::NEG_INFINITY".to_string(),
+        (Language::C, v) if v.is_nan() => "NAN".to_string(),
+        (Language::C, f64::INFINITY) => "INFINITY".to_string(),
+        (Language::C, f64::NEG_INFINITY) => "(-INFINITY)".to_string(),
+        (_, v) => format!("{v:?}"),
+    }
+}
@@ -0,0 +1,95 @@
+//! Output transformation of LightGBM objectives
+
+use crate::{Error, Result};
+
+/// Transformation from raw scores to predictions, parsed from the `objective` line of a model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ConvertOutput {
+    Identity,
+    Sqrt,
+    Exp,
+    Sigmoid(f64),
+    Softmax,
+    LogExp,
+}
+impl ConvertOutput {
+    pub(crate) fn parse(objective: &str) -> Result<Self> {
+        let mut tokens = objective.split(' ');
+        let name = tokens.next().unwrap_or_default();
+        let mut sqrt = false;
+        let mut sigmoid = 1.0;
+        for token in tokens {
+            if token == "sqrt" {
+                sqrt = true;
+            } else if let Some(value) = token.strip_prefix("sigmoid:") {
+                sigmoid = value
+                    .parse()
+                    .map_err(|_| Error::from_message(&format!("failed to parse `{value}`")))?;
+            }
+        }
+        Ok(match name {
+            "regression" | "regression_l1" | "huber" | "fair" | "quantile" | "mape" => {
+                if sqrt {
+                    Self::Sqrt
+                } else {
+                    Self::Identity
+                }
+            }
+            "poisson" | "gamma" | "tweedie" => Self::Exp,
+            "binary" | "multiclassova" => Self::Sigmoid(sigmoid),
+            "cross_entropy" => Self::Sigmoid(1.0),
+            "multiclass" => Self::Softmax,
+            "cross_entropy_lambda" => Self::LogExp,
+            "lambdarank" | "rank_xendcg" | "custom" => Self::Identity,
+            _ => {
+                return Err(Error::from_message(&format!(
+                    "unsupported objective: {objective}"
+                )));
+            }
+        })
+    }
+    pub(crate) fn apply(self, values: &mut [f64]) {
+        match self {
+            Self::Identity => {}
+            Self::Sqrt => {
+                for x in values {
+                    let sign = if *x > 0.0 {
+                        1.0
+                    } else if *x < 0.0 {
+                        -1.0
+                    } else {
+                        0.0
+                    };
+                    *x = sign * *x * *x;
+                }
+            }
+            Self::Exp => {
+                for x in values {
+                    *x = x.exp();
+                }
+            }
+            Self::Sigmoid(sigmoid) => {
+                for x in values {
+                    *x = 1.0 / (1.0 + (-sigmoid * *x).exp());
+                }
+            }
+            Self::Softmax => {
+                let max = values.iter().copied().fold(values[0], f64::max);
+                let mut sum = 0.0;
+                for x in values.iter_mut() {
+                    *x = (*x - max).exp();
+                    sum += *x;
+                }
+                for x in values.iter_mut() {
+                    *x /= sum;
+                }
+            }
+            Self::LogExp => {
+                for x in values {
+                    let y = 1.0 + x.exp();
+                    *x = if y > 0.0 { y.ln() } else { f64::NEG_INFINITY };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Cross-validation
+
+use crate::{Booster, Dataset, Error, Field, Parameters, Result};
+use derive_ex::derive_ex;
+use std::{ops::Range, sync::Arc};
+
+/// Row indices of a fold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fold {
+    pub train: Vec<i32>,
+    pub valid: Vec<i32>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Folds {
+    /// Split rows into the specified number of folds.
+    ///
+    /// If the dataset has [`Field::GROUP`], each query is kept in a single fold.
+    /// Otherwise, folds are stratified by label according to [`CvOptions::stratified`].
+    NumFold(usize),
+
+    /// Explicit training and validation indices for each fold.
+    Explicit(Vec<Fold>),
+}
+
+#[derive(Clone, Debug)]
+#[derive_ex(Default)]
+pub struct CvOptions {
+    #[default(100)]
+    pub num_boost_round: usize,
+
+    #[default(Folds::NumFold(5))]
+    pub folds: Folds,
+
+    /// Whether to stratify folds by label.
+    ///
+    /// If `None`, folds are stratified when the objective is binary or multiclass.
+    pub stratified: Option<bool>,
+
+    /// Whether to shuffle rows before splitting.
+    #[default(true)]
+    pub shuffle: bool,
+
+    pub seed: u64,
+}
+
+/// Mean and standard deviation of a metric over folds for each iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CvMetric {
+    pub name: String,
+    pub mean: Vec<f64>,
+    pub stdv: Vec<f64>,
+}
+
+pub struct CvResult {
+    pub metrics: Vec<CvMetric>,
+
+    /// Booster trained on each fold.
+    pub boosters: Vec<Booster>,
+}
+
+/// Train a booster on each fold in lockstep and evaluate metrics on the validation data of each fold.
+///
+/// Similar to [`lightgbm.cv`](https://lightgbm.readthedocs.io/en/latest/pythonapi/lightgbm.cv.html) of the Python package.
+pub fn cv(train: &Dataset, parameters: &Parameters, options: &CvOptions) -> Result<CvResult> {
+    let folds = make_folds(train, parameters, options)?;
+    let mut boosters = Vec::with_capacity(folds.len());
+    for fold in &folds {
+        let train_data = train.subset(&fold.train, parameters)?;
+        let valid_data = train.subset(&fold.valid, parameters)?;
+        let mut b = Booster::new(Arc::new(train_data), parameters)?;
+        b.add_valid_data(Arc::new(valid_data))?;
+        boosters.push(b);
+    }
+    let mut metrics = boosters[0]
+        .get_eval_names()?
+        .into_iter()
+        .map(|name| CvMetric {
+            name,
+            mean: Vec::new(),
+            stdv: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for _ in 0..options.num_boost_round {
+        let mut is_finished = true;
+        for b in &mut boosters {
+            is_finished &= b.update_one_iter()?;
+        }
+        if is_finished {
+            break;
+        }
+        let evals = boosters
+            .iter()
+            .map(|b| b.get_eval(1))
+            .collect::<Result<Vec<_>>>()?;
+        for (i, metric) in metrics.iter_mut().enumerate() {
+            let (mean, stdv) = mean_stdv(evals.iter().map(|e| e[i]));
+            metric.mean.push(mean);
+            metric.stdv.push(stdv);
+        }
+    }
+    Ok(CvResult { metrics, boosters })
+}
+
+/// Returns the folds used by [`cv`].
+pub fn make_folds(
+    train: &Dataset,
+    parameters: &Parameters,
+    options: &CvOptions,
+) -> Result<Vec<Fold>> {
+    let num_data = train.get_num_data()?;
+    let num_fold = match &options.folds {
+        Folds::NumFold(num_fold) => *num_fold,
+        Folds::Explicit(folds) => return check_folds(folds, num_data),
+    };
+    if num_fold < 2 {
+        return Err(Error::from_message("num_fold must be at least 2"));
+    }
+    let group = train.get_field(Field::GROUP)?;
+    let units: Vec<Range<usize>> = if group.is_empty() {
+        (0..num_data).map(|i| i..i + 1).collect()
+    } else {
+        group
+            .windows(2)
+            .map(|w| w[0] as usize..w[1] as usize)
+            .collect()
+    };
+    if units.len() < num_fold {
+        return Err(Error::from_message(&format!(
+            "num_fold must be less than or equal to {}",
+            units.len()
+        )));
+    }
+    let mut order = (0..units.len()).collect::<Vec<_>>();
+    if options.shuffle {
+        SplitMix64(options.seed).shuffle(&mut order);
+    }
+    let stratified = options
+        .stratified
+        .unwrap_or_else(|| is_classification(parameters));
+    let mut unit_folds = vec![0; units.len()];
+    if stratified && group.is_empty() {
+        let label = train.get_field(Field::LABEL)?;
+        order.sort_by(|&i, &j| label[i].total_cmp(&label[j]));
+        for (i, &unit) in order.iter().enumerate() {
+            unit_folds[unit] = i % num_fold;
+        }
+    } else {
+        for (i, &unit) in order.iter().enumerate() {
+            unit_folds[unit] = i * num_fold / units.len();
+        }
+    }
+    let mut folds = vec![
+        Fold {
+            train: Vec::new(),
+            valid: Vec::new(),
+        };
+        num_fold
+    ];
+    for (unit, rows) in units.into_iter().enumerate() {
+        for row in rows {
+            let row = row.try_into()?;
+            for (f, fold) in folds.iter_mut().enumerate() {
+                if f == unit_folds[unit] {
+                    fold.valid.push(row);
+                } else {
+                    fold.train.push(row);
+                }
+            }
+        }
+    }
+    Ok(folds)
+}
+
+fn check_folds(folds: &[Fold], num_data: usize) -> Result<Vec<Fold>> {
+    if folds.is_empty() {
+        return Err(Error::from_message("folds must not be empty"));
+    }
+    let mut folds = folds.to_vec();
+    for fold in &mut folds {
+        for indices in [&mut fold.train, &mut fold.valid] {
+            indices.sort_unstable();
+            if let Some(&index) = indices
+                .iter()
+                .find(|&&index| index < 0 || index as usize >= num_data)
+            {
+                return Err(Error::from_message(&format!(
+                    "index out of bounds: the num_data is {num_data} but the index is {index}"
+                )));
+            }
+        }
+    }
+    Ok(folds)
+}
+
+fn is_classification(parameters: &Parameters) -> bool {
+    const OBJECTIVE_KEYS: &[&str] = &["objective", "objective_type", "app", "application", "loss"];
+    const CLASSIFICATION_OBJECTIVES: &[&str] = &[
+        "binary",
+        "multiclass",
+        "softmax",
+        "multiclassova",
+        "multiclass_ova",
+        "ova",
+        "ovr",
+    ];
+    OBJECTIVE_KEYS
+        .iter()
+        .find_map(|key| parameters.get(key))
+        .is_some_and(|value| CLASSIFICATION_OBJECTIVES.contains(&value.to_string().as_str()))
+}
+
+fn mean_stdv(values: impl ExactSizeIterator<Item = f64> + Clone) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.clone().sum::<f64>() / n;
+    let var = values.map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            values.swap(i, j);
+        }
+    }
+}
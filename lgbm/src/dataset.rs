@@ -1,15 +1,17 @@
 use crate::{
     Error, Parameters, Result,
-    mat::AsMat,
+    mat::{AsMat, AsSparseMat, Csc, Csr},
     to_result,
     utils::{get_strings, path_to_cstring, to_cstring},
 };
 use lgbm_sys::{
-    C_API_DTYPE_FLOAT32, C_API_DTYPE_FLOAT64, C_API_DTYPE_INT32, C_API_DTYPE_INT64, DatasetHandle,
-    LGBM_DatasetCreateFromFile, LGBM_DatasetCreateFromMat, LGBM_DatasetCreateFromMats,
-    LGBM_DatasetDumpText, LGBM_DatasetFree, LGBM_DatasetGetFeatureNames, LGBM_DatasetGetField,
-    LGBM_DatasetGetNumData, LGBM_DatasetGetNumFeature, LGBM_DatasetSetFeatureNames,
-    LGBM_DatasetSetField,
+    ByteBufferHandle, C_API_DTYPE_FLOAT32, C_API_DTYPE_FLOAT64, C_API_DTYPE_INT32,
+    C_API_DTYPE_INT64, DatasetHandle, LGBM_ByteBufferFree, LGBM_ByteBufferGetAt,
+    LGBM_DatasetCreateFromCSC, LGBM_DatasetCreateFromCSR, LGBM_DatasetCreateFromFile,
+    LGBM_DatasetCreateFromMat, LGBM_DatasetCreateFromMats, LGBM_DatasetDumpText, LGBM_DatasetFree,
+    LGBM_DatasetGetFeatureNames, LGBM_DatasetGetField, LGBM_DatasetGetNumData,
+    LGBM_DatasetGetNumFeature, LGBM_DatasetGetSubset, LGBM_DatasetSaveBinary,
+    LGBM_DatasetSerializeReferenceToBinary, LGBM_DatasetSetFeatureNames, LGBM_DatasetSetField,
 };
 use std::{
     marker::PhantomData,
@@ -53,6 +55,27 @@ pub trait FeatureData: Data {}
 impl FeatureData for f32 {}
 impl FeatureData for f64 {}
 
+pub trait IndPtrData: Data + Copy {
+    fn to_usize(self) -> Option<usize>;
+    fn from_usize(value: usize) -> Option<Self>;
+}
+impl IndPtrData for i32 {
+    fn to_usize(self) -> Option<usize> {
+        self.try_into().ok()
+    }
+    fn from_usize(value: usize) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+impl IndPtrData for i64 {
+    fn to_usize(self) -> Option<usize> {
+        self.try_into().ok()
+    }
+    fn from_usize(value: usize) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Field<T> {
     name: &'static [u8],
@@ -105,6 +128,13 @@ impl Dataset {
         Ok(Self(handle))
     }
 
+    /// Load a dataset saved by [`Dataset::save_binary`].
+    ///
+    /// [LGBM_DatasetCreateFromFile](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateFromFile) detects the binary format automatically.
+    pub fn from_binary(filename: &Path, parameters: &Parameters) -> Result<Self> {
+        Self::from_file(filename, None, parameters)
+    }
+
     /// [LGBM_DatasetCreateFromMat](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateFromMat)
     #[doc(alias = "LGBM_DatasetCreateFromMat")]
     pub fn from_mat<T: FeatureData>(
@@ -171,6 +201,95 @@ impl Dataset {
         Ok(Self(handle))
     }
 
+    /// [LGBM_DatasetCreateFromCSR](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateFromCSR)
+    #[doc(alias = "LGBM_DatasetCreateFromCSR")]
+    pub fn from_csr<T: FeatureData, P: IndPtrData>(
+        csr: impl AsSparseMat<T, P, Layout = Csr>,
+        reference: Option<&Dataset>,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        let csr = csr.as_sparse_mat();
+        let mut handle = null_mut();
+        unsafe {
+            to_result(LGBM_DatasetCreateFromCSR(
+                csr.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csr.indices().as_ptr(),
+                csr.as_data_ptr(),
+                T::DATA_TYPE,
+                csr.indptr().len().try_into()?,
+                csr.nnz().try_into()?,
+                csr.ncol().try_into()?,
+                parameters.to_cstring()?.as_ptr(),
+                to_dataset_handle(reference),
+                &mut handle,
+            ))?;
+        }
+        Ok(Self(handle))
+    }
+
+    /// [LGBM_DatasetCreateFromCSC](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateFromCSC)
+    #[doc(alias = "LGBM_DatasetCreateFromCSC")]
+    pub fn from_csc<T: FeatureData, P: IndPtrData>(
+        csc: impl AsSparseMat<T, P, Layout = Csc>,
+        reference: Option<&Dataset>,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        let csc = csc.as_sparse_mat();
+        let mut handle = null_mut();
+        unsafe {
+            to_result(LGBM_DatasetCreateFromCSC(
+                csc.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csc.indices().as_ptr(),
+                csc.as_data_ptr(),
+                T::DATA_TYPE,
+                csc.indptr().len().try_into()?,
+                csc.nnz().try_into()?,
+                csc.nrow().try_into()?,
+                parameters.to_cstring()?.as_ptr(),
+                to_dataset_handle(reference),
+                &mut handle,
+            ))?;
+        }
+        Ok(Self(handle))
+    }
+
+    /// [LGBM_DatasetGetSubset](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetGetSubset)
+    ///
+    /// `indices` must be sorted and in the range of `0..get_num_data()`.
+    /// The subset shares bin mappers with `self`, and label, weight, init score and group are carried over.
+    #[doc(alias = "LGBM_DatasetGetSubset")]
+    pub fn subset(&self, indices: &[i32], parameters: &Parameters) -> Result<Self> {
+        if indices.is_empty() {
+            return Err(Error::from_message("indices must not be empty"));
+        }
+        let num_data = self.get_num_data()?;
+        let mut last = 0;
+        for &index in indices {
+            if index < 0 || index as usize >= num_data {
+                return Err(Error::from_message(&format!(
+                    "index out of bounds: the num_data is {num_data} but the index is {index}"
+                )));
+            }
+            if index < last {
+                return Err(Error::from_message("indices must be sorted"));
+            }
+            last = index;
+        }
+        let mut handle = null_mut();
+        unsafe {
+            to_result(LGBM_DatasetGetSubset(
+                self.0,
+                indices.as_ptr(),
+                indices.len().try_into()?,
+                parameters.to_cstring()?.as_ptr(),
+                &mut handle,
+            ))?;
+        }
+        Ok(Self(handle))
+    }
+
     /// [LGBM_DatasetSetField](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetSetField)
     #[doc(alias = "LGBM_DatasetSetField")]
     pub fn set_field<T: Data>(&mut self, field: Field<T>, data: &[T]) -> Result<()> {
@@ -199,6 +318,9 @@ impl Dataset {
                 &mut out_ptr,
                 &mut out_type,
             ))?;
+            if out_ptr.is_null() {
+                return Ok(&[]);
+            }
             if out_type != T::DATA_TYPE {
                 return Err(Error::from_message("element type mismatch"));
             }
@@ -237,6 +359,37 @@ impl Dataset {
         }
     }
 
+    /// [LGBM_DatasetSaveBinary](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetSaveBinary)
+    #[doc(alias = "LGBM_DatasetSaveBinary")]
+    pub fn save_binary(&self, filename: &Path) -> Result<()> {
+        unsafe {
+            to_result(LGBM_DatasetSaveBinary(
+                self.0,
+                path_to_cstring(filename)?.as_ptr(),
+            ))
+        }
+    }
+
+    /// [LGBM_DatasetSerializeReferenceToBinary](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetSerializeReferenceToBinary)
+    ///
+    /// The result can be passed to [`DatasetBuilder::from_serialized_reference`](crate::DatasetBuilder::from_serialized_reference).
+    #[doc(alias = "LGBM_DatasetSerializeReferenceToBinary")]
+    pub fn serialize_reference(&self) -> Result<ByteBuffer> {
+        let mut handle = null_mut();
+        let mut out_len = 0;
+        unsafe {
+            to_result(LGBM_DatasetSerializeReferenceToBinary(
+                self.0,
+                &mut handle,
+                &mut out_len,
+            ))?;
+        }
+        Ok(ByteBuffer {
+            handle,
+            len: out_len as usize,
+        })
+    }
+
     /// [LGBM_DatasetSetFeatureNames](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetSetFeatureNames)
     #[doc(alias = "LGBM_DatasetSetFeatureNames")]
     pub fn set_feature_names<T: AsRef<str>>(
@@ -284,6 +437,53 @@ impl Drop for Dataset {
 unsafe impl Send for Dataset {}
 unsafe impl Sync for Dataset {}
 
+/// Owned [ByteBufferHandle](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.ByteBufferHandle)
+pub struct ByteBuffer {
+    handle: ByteBufferHandle,
+    len: usize,
+}
+impl ByteBuffer {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// [LGBM_ByteBufferGetAt](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_ByteBufferGetAt)
+    #[doc(alias = "LGBM_ByteBufferGetAt")]
+    pub fn get(&self, index: usize) -> Result<u8> {
+        if index >= self.len {
+            return Err(Error::from_message(&format!(
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len
+            )));
+        }
+        let mut out_val = 0;
+        unsafe {
+            to_result(LGBM_ByteBufferGetAt(
+                self.handle,
+                index.try_into()?,
+                &mut out_val,
+            ))?;
+        }
+        Ok(out_val)
+    }
+
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        (0..self.len).map(|index| self.get(index)).collect()
+    }
+}
+impl Drop for ByteBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            to_result(LGBM_ByteBufferFree(self.handle)).unwrap();
+        }
+    }
+}
+unsafe impl Send for ByteBuffer {}
+unsafe impl Sync for ByteBuffer {}
+
 fn to_dataset_handle(dataset: Option<&Dataset>) -> DatasetHandle {
     if let Some(dataset) = dataset {
         dataset.0
@@ -0,0 +1,307 @@
+use crate::{
+    Dataset, Error, FeatureData, IndPtrData, Parameters, Result,
+    mat::{AsMat, AsSparseMat, Csr, RowMajor},
+    to_result,
+    utils::bool_to_int,
+};
+use derive_ex::derive_ex;
+use lgbm_sys::{
+    LGBM_DatasetCreateByReference, LGBM_DatasetCreateFromSerializedReference,
+    LGBM_DatasetInitStreaming, LGBM_DatasetMarkFinished, LGBM_DatasetPushRows,
+    LGBM_DatasetPushRowsByCSR, LGBM_DatasetPushRowsByCSRWithMetadata,
+    LGBM_DatasetPushRowsWithMetadata, LGBM_DatasetSetWaitForManualFinish,
+};
+use std::{
+    os::raw::c_void,
+    ptr::{null, null_mut},
+};
+
+/// Metadata fields pushed together with rows by [`DatasetBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive_ex(Default)]
+pub struct StreamingOptions {
+    pub has_weights: bool,
+    pub has_init_scores: bool,
+    pub has_queries: bool,
+
+    /// Number of initial score classes
+    #[default(1)]
+    pub num_class: usize,
+}
+
+/// Metadata of a batch of rows pushed by [`DatasetBuilder`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RowsMetadata<'a> {
+    /// One label per row.
+    pub label: &'a [f32],
+
+    /// One weight per row. Required if [`StreamingOptions::has_weights`] is set.
+    pub weight: Option<&'a [f32]>,
+
+    /// `nrow * num_class` initial scores in column format.
+    /// Required if [`StreamingOptions::has_init_scores`] is set.
+    pub init_score: Option<&'a [f64]>,
+
+    /// One query value per row. Required if [`StreamingOptions::has_queries`] is set.
+    pub query: Option<&'a [i32]>,
+}
+
+/// Builder of a [`Dataset`] that receives rows in batches.
+///
+/// The dataset shares bin mappers with the reference dataset and is available only after [`DatasetBuilder::finish`].
+pub struct DatasetBuilder {
+    dataset: Dataset,
+    options: StreamingOptions,
+    num_feature: usize,
+    num_total_row: usize,
+    num_pushed_row: usize,
+}
+
+impl DatasetBuilder {
+    /// [LGBM_DatasetCreateByReference](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateByReference)
+    /// and [LGBM_DatasetInitStreaming](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetInitStreaming)
+    #[doc(alias = "LGBM_DatasetCreateByReference")]
+    #[doc(alias = "LGBM_DatasetInitStreaming")]
+    pub fn new(
+        reference: &Dataset,
+        num_total_row: usize,
+        options: StreamingOptions,
+    ) -> Result<Self> {
+        let mut handle = null_mut();
+        unsafe {
+            to_result(LGBM_DatasetCreateByReference(
+                reference.0,
+                num_total_row.try_into()?,
+                &mut handle,
+            ))?;
+        }
+        Self::from_dataset(Dataset(handle), num_total_row, options)
+    }
+
+    /// [LGBM_DatasetCreateFromSerializedReference](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetCreateFromSerializedReference)
+    /// and [LGBM_DatasetInitStreaming](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetInitStreaming)
+    ///
+    /// `reference` is the output of [`Dataset::serialize_reference`].
+    #[doc(alias = "LGBM_DatasetCreateFromSerializedReference")]
+    pub fn from_serialized_reference(
+        reference: &[u8],
+        num_total_row: usize,
+        options: StreamingOptions,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        let mut handle = null_mut();
+        unsafe {
+            to_result(LGBM_DatasetCreateFromSerializedReference(
+                reference.as_ptr() as *const c_void,
+                reference.len().try_into()?,
+                num_total_row.try_into()?,
+                options.num_class.try_into()?,
+                parameters.to_cstring()?.as_ptr(),
+                &mut handle,
+            ))?;
+        }
+        Self::from_dataset(Dataset(handle), num_total_row, options)
+    }
+
+    fn from_dataset(
+        dataset: Dataset,
+        num_total_row: usize,
+        options: StreamingOptions,
+    ) -> Result<Self> {
+        unsafe {
+            to_result(LGBM_DatasetInitStreaming(
+                dataset.0,
+                bool_to_int(options.has_weights),
+                bool_to_int(options.has_init_scores),
+                bool_to_int(options.has_queries),
+                options.num_class.try_into()?,
+                1,
+                -1,
+            ))?;
+            to_result(LGBM_DatasetSetWaitForManualFinish(dataset.0, 1))?;
+        }
+        Ok(Self {
+            num_feature: dataset.get_num_feature()?,
+            dataset,
+            options,
+            num_total_row,
+            num_pushed_row: 0,
+        })
+    }
+
+    pub fn num_total_row(&self) -> usize {
+        self.num_total_row
+    }
+    pub fn num_pushed_row(&self) -> usize {
+        self.num_pushed_row
+    }
+
+    /// [LGBM_DatasetPushRows](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetPushRows)
+    #[doc(alias = "LGBM_DatasetPushRows")]
+    pub fn push_rows<T: FeatureData>(
+        &mut self,
+        mat: impl AsMat<T, Layout = RowMajor>,
+    ) -> Result<()> {
+        let mat = mat.as_mat();
+        self.check_rows(mat.nrow(), mat.ncol())?;
+        unsafe {
+            to_result(LGBM_DatasetPushRows(
+                self.dataset.0,
+                mat.as_data_ptr(),
+                T::DATA_TYPE,
+                mat.nrow().try_into()?,
+                mat.ncol().try_into()?,
+                self.num_pushed_row.try_into()?,
+            ))?;
+        }
+        self.num_pushed_row += mat.nrow();
+        Ok(())
+    }
+
+    /// [LGBM_DatasetPushRowsWithMetadata](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetPushRowsWithMetadata)
+    #[doc(alias = "LGBM_DatasetPushRowsWithMetadata")]
+    pub fn push_rows_with_metadata<T: FeatureData>(
+        &mut self,
+        mat: impl AsMat<T, Layout = RowMajor>,
+        metadata: RowsMetadata,
+    ) -> Result<()> {
+        let mat = mat.as_mat();
+        self.check_rows(mat.nrow(), mat.ncol())?;
+        self.check_metadata(mat.nrow(), &metadata)?;
+        unsafe {
+            to_result(LGBM_DatasetPushRowsWithMetadata(
+                self.dataset.0,
+                mat.as_data_ptr(),
+                T::DATA_TYPE,
+                mat.nrow().try_into()?,
+                mat.ncol().try_into()?,
+                self.num_pushed_row.try_into()?,
+                metadata.label.as_ptr(),
+                as_ptr_or_null(metadata.weight),
+                as_ptr_or_null(metadata.init_score),
+                as_ptr_or_null(metadata.query),
+                0,
+            ))?;
+        }
+        self.num_pushed_row += mat.nrow();
+        Ok(())
+    }
+
+    /// [LGBM_DatasetPushRowsByCSR](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetPushRowsByCSR)
+    #[doc(alias = "LGBM_DatasetPushRowsByCSR")]
+    pub fn push_rows_by_csr<T: FeatureData, P: IndPtrData>(
+        &mut self,
+        csr: impl AsSparseMat<T, P, Layout = Csr>,
+    ) -> Result<()> {
+        let csr = csr.as_sparse_mat();
+        self.check_rows(csr.nrow(), csr.ncol())?;
+        unsafe {
+            to_result(LGBM_DatasetPushRowsByCSR(
+                self.dataset.0,
+                csr.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csr.indices().as_ptr(),
+                csr.as_data_ptr(),
+                T::DATA_TYPE,
+                csr.indptr().len().try_into()?,
+                csr.nnz().try_into()?,
+                csr.ncol().try_into()?,
+                self.num_pushed_row.try_into()?,
+            ))?;
+        }
+        self.num_pushed_row += csr.nrow();
+        Ok(())
+    }
+
+    /// [LGBM_DatasetPushRowsByCSRWithMetadata](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetPushRowsByCSRWithMetadata)
+    #[doc(alias = "LGBM_DatasetPushRowsByCSRWithMetadata")]
+    pub fn push_rows_by_csr_with_metadata<T: FeatureData, P: IndPtrData>(
+        &mut self,
+        csr: impl AsSparseMat<T, P, Layout = Csr>,
+        metadata: RowsMetadata,
+    ) -> Result<()> {
+        let csr = csr.as_sparse_mat();
+        self.check_rows(csr.nrow(), csr.ncol())?;
+        self.check_metadata(csr.nrow(), &metadata)?;
+        unsafe {
+            to_result(LGBM_DatasetPushRowsByCSRWithMetadata(
+                self.dataset.0,
+                csr.as_indptr_ptr(),
+                P::DATA_TYPE,
+                csr.indices().as_ptr(),
+                csr.as_data_ptr(),
+                T::DATA_TYPE,
+                csr.indptr().len().try_into()?,
+                csr.nnz().try_into()?,
+                self.num_pushed_row.try_into()?,
+                metadata.label.as_ptr(),
+                as_ptr_or_null(metadata.weight),
+                as_ptr_or_null(metadata.init_score),
+                as_ptr_or_null(metadata.query),
+                0,
+            ))?;
+        }
+        self.num_pushed_row += csr.nrow();
+        Ok(())
+    }
+
+    /// [LGBM_DatasetMarkFinished](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_DatasetMarkFinished)
+    #[doc(alias = "LGBM_DatasetMarkFinished")]
+    pub fn finish(self) -> Result<Dataset> {
+        if self.num_pushed_row != self.num_total_row {
+            return Err(Error::from_message(&format!(
+                "{} rows must be pushed, but got {}",
+                self.num_total_row, self.num_pushed_row
+            )));
+        }
+        unsafe {
+            to_result(LGBM_DatasetMarkFinished(self.dataset.0))?;
+        }
+        Ok(self.dataset)
+    }
+
+    fn check_rows(&self, nrow: usize, ncol: usize) -> Result<()> {
+        if ncol != self.num_feature {
+            return Err(Error::from_message(&format!(
+                "column size must be {}, but got {ncol}",
+                self.num_feature
+            )));
+        }
+        if self.num_pushed_row + nrow > self.num_total_row {
+            return Err(Error::from_message(&format!(
+                "too many rows : (num_total_row = {}, num_pushed_row = {}, nrow = {nrow})",
+                self.num_total_row, self.num_pushed_row
+            )));
+        }
+        Ok(())
+    }
+    fn check_metadata(&self, nrow: usize, metadata: &RowsMetadata) -> Result<()> {
+        check_len("label", Some(metadata.label), true, nrow)?;
+        check_len("weight", metadata.weight, self.options.has_weights, nrow)?;
+        check_len(
+            "init_score",
+            metadata.init_score,
+            self.options.has_init_scores,
+            nrow * self.options.num_class,
+        )?;
+        check_len("query", metadata.query, self.options.has_queries, nrow)
+    }
+}
+
+fn check_len<T>(name: &str, data: Option<&[T]>, required: bool, len: usize) -> Result<()> {
+    match (data, required) {
+        (Some(data), true) if data.len() != len => Err(Error::from_message(&format!(
+            "{name} length must be {len}, but got {}",
+            data.len()
+        ))),
+        (Some(_), true) | (None, false) => Ok(()),
+        (None, true) => Err(Error::from_message(&format!("{name} is required"))),
+        (Some(_), false) => Err(Error::from_message(&format!(
+            "{name} is not enabled in StreamingOptions"
+        ))),
+    }
+}
+
+fn as_ptr_or_null<T>(data: Option<&[T]>) -> *const T {
+    data.map_or(null(), |data| data.as_ptr())
+}
@@ -1,6 +1,9 @@
+#[cfg(feature = "lightgbm")]
 use lgbm_sys::LGBM_GetLastError;
+#[cfg(feature = "lightgbm")]
+use std::ffi::CStr;
 use std::{
-    ffi::{CStr, FromBytesWithNulError, FromVecWithNulError, IntoStringError, NulError},
+    ffi::{FromBytesWithNulError, FromVecWithNulError, IntoStringError, NulError},
     num::TryFromIntError,
     os::raw::c_int,
     string::FromUtf8Error,
@@ -38,6 +41,7 @@ impl std::fmt::Display for Error {
 }
 impl std::error::Error for Error {}
 
+#[cfg(feature = "lightgbm")]
 pub(crate) fn to_result(code: c_int) -> Result<()> {
     if code == 0 {
         Ok(())
@@ -84,3 +88,8 @@ impl From<FromUtf8Error> for Error {
         Self::from_error(value)
     }
 }
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::from_error(value)
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{Booster, Data, Error, FeatureData, Parameters, PredictType, Result, to_result};
+use lgbm_sys::{
+    FastConfigHandle, LGBM_BoosterPredictForCSRSingleRowFast,
+    LGBM_BoosterPredictForCSRSingleRowFastInit, LGBM_BoosterPredictForMatSingleRowFast,
+    LGBM_BoosterPredictForMatSingleRowFastInit, LGBM_FastConfigFree,
+};
+use std::{marker::PhantomData, ptr::null_mut};
+
+/// Owned [FastConfigHandle](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.FastConfigHandle)
+struct FastConfig {
+    handle: FastConfigHandle,
+    num_feature: usize,
+    num_predict: usize,
+}
+impl FastConfig {
+    fn new(
+        booster: &Booster,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        init: impl FnOnce(&Booster, usize, *mut FastConfigHandle) -> Result<()>,
+    ) -> Result<Self> {
+        let num_feature = booster.get_num_feature()?;
+        let num_predict =
+            booster.calc_num_predict(1, predict_type, start_iteration, num_iteration)?;
+        let mut handle = null_mut();
+        init(booster, num_feature, &mut handle)?;
+        Ok(Self {
+            handle,
+            num_feature,
+            num_predict,
+        })
+    }
+
+    fn check_out(&self, out: &[f64]) -> Result<()> {
+        if out.len() != self.num_predict {
+            return Err(Error::from_message(&format!(
+                "output size must be {}, but got {}",
+                self.num_predict,
+                out.len()
+            )));
+        }
+        Ok(())
+    }
+}
+impl Drop for FastConfig {
+    fn drop(&mut self) {
+        unsafe {
+            to_result(LGBM_FastConfigFree(self.handle)).unwrap();
+        }
+    }
+}
+
+/// Predictor for a single dense row with a reusable fast config.
+///
+/// The prediction type, iteration range and parameters are fixed at construction,
+/// so [`FastPredictor::predict`] does not parse parameters or allocate.
+pub struct FastPredictor<'a, T: FeatureData> {
+    config: FastConfig,
+    _phantom: PhantomData<(&'a Booster, T)>,
+}
+impl<'a, T: FeatureData> FastPredictor<'a, T> {
+    /// [LGBM_BoosterPredictForMatSingleRowFastInit](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForMatSingleRowFastInit)
+    #[doc(alias = "LGBM_BoosterPredictForMatSingleRowFastInit")]
+    pub fn new(
+        booster: &'a Booster,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        let config = FastConfig::new(
+            booster,
+            predict_type,
+            start_iteration,
+            num_iteration,
+            |booster, num_feature, handle| unsafe {
+                to_result(LGBM_BoosterPredictForMatSingleRowFastInit(
+                    booster.handle,
+                    predict_type.to_cint(),
+                    start_iteration.try_into()?,
+                    num_iteration.unwrap_or(0).try_into()?,
+                    T::DATA_TYPE,
+                    num_feature.try_into()?,
+                    parameters.to_cstring()?.as_ptr(),
+                    handle,
+                ))
+            },
+        )?;
+        Ok(Self {
+            config,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Number of values written to `out` by [`FastPredictor::predict`].
+    pub fn num_predict(&self) -> usize {
+        self.config.num_predict
+    }
+
+    /// [LGBM_BoosterPredictForMatSingleRowFast](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForMatSingleRowFast)
+    #[doc(alias = "LGBM_BoosterPredictForMatSingleRowFast")]
+    pub fn predict(&mut self, row: &[T], out: &mut [f64]) -> Result<()> {
+        let num_feature = self.config.num_feature;
+        if row.len() != num_feature {
+            return Err(Error::from_message(&format!(
+                "column size must be {num_feature}, but got {}",
+                row.len(),
+            )));
+        }
+        self.config.check_out(out)?;
+        let mut out_len = 0;
+        unsafe {
+            to_result(LGBM_BoosterPredictForMatSingleRowFast(
+                self.config.handle,
+                T::as_data_ptr(row.as_ptr()),
+                &mut out_len,
+                out.as_mut_ptr(),
+            ))?;
+        }
+        assert_eq!(out_len as usize, out.len());
+        Ok(())
+    }
+}
+unsafe impl<T: FeatureData> Send for FastPredictor<'_, T> {}
+unsafe impl<T: FeatureData> Sync for FastPredictor<'_, T> {}
+
+/// Predictor for a single sparse row with a reusable fast config.
+///
+/// The sparse counterpart of [`FastPredictor`].
+pub struct FastCsrPredictor<'a, T: FeatureData> {
+    config: FastConfig,
+    _phantom: PhantomData<(&'a Booster, T)>,
+}
+impl<'a, T: FeatureData> FastCsrPredictor<'a, T> {
+    /// [LGBM_BoosterPredictForCSRSingleRowFastInit](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForCSRSingleRowFastInit)
+    #[doc(alias = "LGBM_BoosterPredictForCSRSingleRowFastInit")]
+    pub fn new(
+        booster: &'a Booster,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        parameters: &Parameters,
+    ) -> Result<Self> {
+        let config = FastConfig::new(
+            booster,
+            predict_type,
+            start_iteration,
+            num_iteration,
+            |booster, num_feature, handle| unsafe {
+                to_result(LGBM_BoosterPredictForCSRSingleRowFastInit(
+                    booster.handle,
+                    predict_type.to_cint(),
+                    start_iteration.try_into()?,
+                    num_iteration.unwrap_or(0).try_into()?,
+                    T::DATA_TYPE,
+                    num_feature.try_into()?,
+                    parameters.to_cstring()?.as_ptr(),
+                    handle,
+                ))
+            },
+        )?;
+        Ok(Self {
+            config,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Number of values written to `out` by [`FastCsrPredictor::predict`].
+    pub fn num_predict(&self) -> usize {
+        self.config.num_predict
+    }
+
+    /// [LGBM_BoosterPredictForCSRSingleRowFast](https://lightgbm.readthedocs.io/en/latest/C-API.html#c.LGBM_BoosterPredictForCSRSingleRowFast)
+    ///
+    /// `indices` are the feature indices of the non-zero `values` in the row.
+    #[doc(alias = "LGBM_BoosterPredictForCSRSingleRowFast")]
+    pub fn predict(&mut self, indices: &[i32], values: &[T], out: &mut [f64]) -> Result<()> {
+        if indices.len() != values.len() {
+            return Err(Error::from_message(&format!(
+                "mismatch length : (indices.len() = {}, values.len() = {})",
+                indices.len(),
+                values.len()
+            )));
+        }
+        let num_feature = self.config.num_feature;
+        for &index in indices {
+            if index < 0 || index as usize >= num_feature {
+                return Err(Error::from_message(&format!(
+                    "index out of bounds: the len is {num_feature} but the index is {index}"
+                )));
+            }
+        }
+        self.config.check_out(out)?;
+        let nelem: i64 = values.len().try_into()?;
+        let indptr = [0, nelem];
+        let mut out_len = 0;
+        unsafe {
+            to_result(LGBM_BoosterPredictForCSRSingleRowFast(
+                self.config.handle,
+                i64::as_data_ptr(indptr.as_ptr()),
+                i64::DATA_TYPE,
+                indices.as_ptr(),
+                T::as_data_ptr(values.as_ptr()),
+                2,
+                nelem,
+                &mut out_len,
+                out.as_mut_ptr(),
+            ))?;
+        }
+        assert_eq!(out_len as usize, out.len());
+        Ok(())
+    }
+}
+unsafe impl<T: FeatureData> Send for FastCsrPredictor<'_, T> {}
+unsafe impl<T: FeatureData> Sync for FastCsrPredictor<'_, T> {}
@@ -1,20 +1,52 @@
 //! Unofficial Rust bindings for [LightGBM](https://lightgbm.readthedocs.io/en/latest/)
 
+#[cfg(feature = "lightgbm")]
 mod booster;
+pub mod codegen;
+mod convert_output;
+#[cfg(feature = "lightgbm")]
+pub mod cv;
+#[cfg(feature = "lightgbm")]
 mod dataset;
+#[cfg(feature = "lightgbm")]
+mod dataset_builder;
 mod error;
+#[cfg(feature = "lightgbm")]
+mod fast_predictor;
+#[cfg(feature = "lightgbm")]
 pub mod mat;
+pub mod model;
+#[cfg(feature = "onnx")]
+pub mod onnx;
 pub mod parameters;
+pub mod pmml;
+mod predict_type;
+mod prediction;
+#[cfg(feature = "predictor")]
+pub mod predictor;
+mod shap;
+#[cfg(feature = "lightgbm")]
+pub mod train;
 
+#[cfg(feature = "lightgbm")]
 pub(crate) mod utils;
 
+#[cfg(feature = "lightgbm")]
 pub use booster::*;
+#[cfg(feature = "lightgbm")]
 pub use dataset::*;
+#[cfg(feature = "lightgbm")]
+pub use dataset_builder::*;
 pub use error::*;
-pub use mat::{AsMat, Mat, MatBuf};
+#[cfg(feature = "lightgbm")]
+pub use fast_predictor::*;
+#[cfg(feature = "lightgbm")]
+pub use mat::{AsMat, AsSparseMat, Mat, MatBuf, SparseMat, SparseMatBuf};
 pub use parameters::Parameters;
+pub use predict_type::*;
+pub use prediction::*;
 
-#[cfg(doctest)]
+#[cfg(all(doctest, feature = "lightgbm"))]
 mod tests {
     #[doc = include_str!("../../README.md")]
     mod readme {}
@@ -1,4 +1,4 @@
-use crate::{Data, Error, Result, utils::bool_to_int};
+use crate::{Data, Error, IndPtrData, MatrixType, Result, utils::bool_to_int};
 use derive_ex::derive_ex;
 use std::{
     ffi::c_void,
@@ -378,6 +378,346 @@ impl<M: AsMat<T>, T> AsMat<T> for &M {
     }
 }
 
+pub trait SparseLayout: Copy + Clone + Send + Sync + Debug {
+    fn matrix_type(&self) -> MatrixType;
+}
+
+/// Compressed sparse row layout
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Csr;
+impl SparseLayout for Csr {
+    #[inline]
+    fn matrix_type(&self) -> MatrixType {
+        MatrixType::Csr
+    }
+}
+
+/// Compressed sparse column layout
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Csc;
+impl SparseLayout for Csc {
+    #[inline]
+    fn matrix_type(&self) -> MatrixType {
+        MatrixType::Csc
+    }
+}
+
+/// Sparse matrix with rows for data and columns for features
+///
+/// `indptr` has `nrow + 1` elements for [`Csr`] and `ncol + 1` elements for [`Csc`].
+#[derive(Clone, Debug)]
+pub struct SparseMatBuf<T, P = i32, L = Csr> {
+    indptr: Vec<P>,
+    indices: Vec<i32>,
+    values: Vec<T>,
+    nrow: usize,
+    ncol: usize,
+    layout: L,
+}
+
+impl<T, P: IndPtrData, L: SparseLayout> SparseMatBuf<T, P, L> {
+    pub fn from_vecs(
+        indptr: Vec<P>,
+        indices: Vec<i32>,
+        values: Vec<T>,
+        nrow: usize,
+        ncol: usize,
+        layout: L,
+    ) -> Result<Self> {
+        validate_sparse(&indptr, &indices, values.len(), nrow, ncol, layout)?;
+        Ok(Self {
+            indptr,
+            indices,
+            values,
+            nrow,
+            ncol,
+            layout,
+        })
+    }
+
+    /// Create a sparse matrix from the non-zero elements of a dense matrix.
+    pub fn from_dense(mat: impl AsMat<T>) -> Result<Self>
+    where
+        T: Clone + Default + PartialEq,
+        L: Default,
+    {
+        let mat = mat.as_mat();
+        let layout = L::default();
+        let (nmajor, nminor) = major_minor(mat.nrow(), mat.ncol(), layout);
+        let zero = T::default();
+        let mut indptr = Vec::with_capacity(nmajor + 1);
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        indptr.push(to_indptr(0)?);
+        for major in 0..nmajor {
+            for minor in 0..nminor {
+                let value = match layout.matrix_type() {
+                    MatrixType::Csr => &mat[[major, minor]],
+                    MatrixType::Csc => &mat[[minor, major]],
+                };
+                if value != &zero {
+                    indices.push(minor.try_into()?);
+                    values.push(value.clone());
+                }
+            }
+            indptr.push(to_indptr(values.len())?);
+        }
+        Ok(Self {
+            indptr,
+            indices,
+            values,
+            nrow: mat.nrow(),
+            ncol: mat.ncol(),
+            layout,
+        })
+    }
+}
+impl<T, P, L: SparseLayout> SparseMatBuf<T, P, L> {
+    pub fn nrow(&self) -> usize {
+        self.nrow
+    }
+    pub fn ncol(&self) -> usize {
+        self.ncol
+    }
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+    pub fn indptr(&self) -> &[P] {
+        &self.indptr
+    }
+    pub fn indices(&self) -> &[i32] {
+        &self.indices
+    }
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+    pub fn matrix_type(&self) -> MatrixType {
+        self.layout.matrix_type()
+    }
+}
+impl<T, P, L: SparseLayout> AsSparseMat<T, P> for SparseMatBuf<T, P, L> {
+    type Layout = L;
+    fn as_sparse_mat(&self) -> SparseMat<'_, T, P, L> {
+        SparseMat {
+            indptr: &self.indptr,
+            indices: &self.indices,
+            values: &self.values,
+            nrow: self.nrow,
+            ncol: self.ncol,
+            layout: self.layout,
+        }
+    }
+}
+
+#[derive_ex(Clone, Copy)]
+pub struct SparseMat<'a, T, P, L: SparseLayout> {
+    indptr: &'a [P],
+    indices: &'a [i32],
+    values: &'a [T],
+    nrow: usize,
+    ncol: usize,
+    layout: L,
+}
+impl<'a, T, P: IndPtrData, L: SparseLayout> SparseMat<'a, T, P, L> {
+    pub fn from_slices(
+        indptr: &'a [P],
+        indices: &'a [i32],
+        values: &'a [T],
+        nrow: usize,
+        ncol: usize,
+        layout: L,
+    ) -> Result<Self> {
+        validate_sparse(indptr, indices, values.len(), nrow, ncol, layout)?;
+        Ok(Self {
+            indptr,
+            indices,
+            values,
+            nrow,
+            ncol,
+            layout,
+        })
+    }
+
+    /// Create a sparse matrix from output of LightGBM without validation.
+    pub(crate) fn from_slices_unchecked(
+        indptr: &'a [P],
+        indices: &'a [i32],
+        values: &'a [T],
+        nrow: usize,
+        ncol: usize,
+        layout: L,
+    ) -> Self {
+        Self {
+            indptr,
+            indices,
+            values,
+            nrow,
+            ncol,
+            layout,
+        }
+    }
+
+    /// Returns an iterator over the non-zero elements as `(row, col, value)`.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &'a T)> + 'a
+    where
+        L: 'a,
+    {
+        let this = *self;
+        let (nmajor, _) = major_minor(self.nrow, self.ncol, self.layout);
+        (0..nmajor).flat_map(move |major| {
+            let (indices, values) = this.major(major);
+            indices.iter().zip(values).map(move |(&minor, value)| {
+                let minor = minor as usize;
+                match this.layout.matrix_type() {
+                    MatrixType::Csr => (major, minor, value),
+                    MatrixType::Csc => (minor, major, value),
+                }
+            })
+        })
+    }
+
+    #[track_caller]
+    fn major(&self, major: usize) -> (&'a [i32], &'a [T]) {
+        let start = self.indptr[major].to_usize().unwrap();
+        let end = self.indptr[major + 1].to_usize().unwrap();
+        (&self.indices[start..end], &self.values[start..end])
+    }
+}
+impl<'a, T, P, L: SparseLayout> SparseMat<'a, T, P, L> {
+    pub fn nrow(&self) -> usize {
+        self.nrow
+    }
+    pub fn ncol(&self) -> usize {
+        self.ncol
+    }
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+    pub fn indptr(&self) -> &'a [P] {
+        self.indptr
+    }
+    pub fn indices(&self) -> &'a [i32] {
+        self.indices
+    }
+    pub fn values(&self) -> &'a [T] {
+        self.values
+    }
+    pub fn matrix_type(&self) -> MatrixType {
+        self.layout.matrix_type()
+    }
+    pub(crate) fn layout(&self) -> L {
+        self.layout
+    }
+    pub(crate) fn as_indptr_ptr(&self) -> *const c_void
+    where
+        P: Data,
+    {
+        P::as_data_ptr(self.indptr.as_ptr())
+    }
+    pub(crate) fn as_data_ptr(&self) -> *const c_void
+    where
+        T: Data,
+    {
+        T::as_data_ptr(self.values.as_ptr())
+    }
+}
+impl<'a, T, P: IndPtrData> SparseMat<'a, T, P, Csr> {
+    /// Returns the column indices and values of the non-zero elements in `row`.
+    #[track_caller]
+    pub fn row(&self, row: usize) -> (&'a [i32], &'a [T]) {
+        assert_row(row, self.nrow);
+        self.major(row)
+    }
+}
+impl<'a, T, P: IndPtrData> SparseMat<'a, T, P, Csc> {
+    /// Returns the row indices and values of the non-zero elements in `col`.
+    #[track_caller]
+    pub fn col(&self, col: usize) -> (&'a [i32], &'a [T]) {
+        assert_col(col, self.ncol);
+        self.major(col)
+    }
+}
+impl<T, P, L: SparseLayout> AsSparseMat<T, P> for SparseMat<'_, T, P, L> {
+    type Layout = L;
+    fn as_sparse_mat(&self) -> SparseMat<'_, T, P, L> {
+        *self
+    }
+}
+
+/// A trait for borrow [`SparseMat`].
+pub trait AsSparseMat<T, P> {
+    type Layout: SparseLayout;
+    fn as_sparse_mat(&self) -> SparseMat<'_, T, P, Self::Layout>;
+}
+
+impl<M: AsSparseMat<T, P>, T, P> AsSparseMat<T, P> for &M {
+    type Layout = M::Layout;
+    fn as_sparse_mat(&self) -> SparseMat<'_, T, P, Self::Layout> {
+        (*self).as_sparse_mat()
+    }
+}
+
+fn major_minor(nrow: usize, ncol: usize, layout: impl SparseLayout) -> (usize, usize) {
+    match layout.matrix_type() {
+        MatrixType::Csr => (nrow, ncol),
+        MatrixType::Csc => (ncol, nrow),
+    }
+}
+
+fn to_indptr<P: IndPtrData>(value: usize) -> Result<P> {
+    P::from_usize(value).ok_or_else(|| Error::from_message("indptr overflow"))
+}
+
+fn validate_sparse<P: IndPtrData>(
+    indptr: &[P],
+    indices: &[i32],
+    nnz: usize,
+    nrow: usize,
+    ncol: usize,
+    layout: impl SparseLayout,
+) -> Result<()> {
+    let (nmajor, nminor) = major_minor(nrow, ncol, layout);
+    if indptr.len() != nmajor + 1 {
+        return Err(Error::from_message(&format!(
+            "indptr length must be {}, but got {}",
+            nmajor + 1,
+            indptr.len()
+        )));
+    }
+    if indices.len() != nnz {
+        return Err(Error::from_message(&format!(
+            "mismatch length : (indices.len() = {}, values.len() = {nnz})",
+            indices.len()
+        )));
+    }
+    let mut last = 0;
+    for (i, p) in indptr.iter().enumerate() {
+        let Some(p) = p.to_usize() else {
+            return Err(Error::from_message("indptr must not be negative"));
+        };
+        if i == 0 && p != 0 {
+            return Err(Error::from_message("indptr must start with 0"));
+        }
+        if p < last {
+            return Err(Error::from_message("indptr must be non-decreasing"));
+        }
+        last = p;
+    }
+    if last != nnz {
+        return Err(Error::from_message(&format!(
+            "last element of indptr must be {nnz}, but got {last}"
+        )));
+    }
+    for &index in indices {
+        if index < 0 || index as usize >= nminor {
+            return Err(Error::from_message(&format!(
+                "index out of bounds: the len is {nminor} but the index is {index}"
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn to_range(value: impl RangeBounds<usize>, len: usize) -> Range<usize> {
     let start = match value.start_bound() {
         Bound::Included(&start) => start,
@@ -0,0 +1,217 @@
+//! Typed representation of the JSON returned by [`Booster::dump_model`](crate::Booster::dump_model)
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::collections::BTreeMap;
+
+/// Values whose absolute value is at most this are treated as zero by LightGBM.
+pub(crate) const ZERO_THRESHOLD: f64 = 1e-35f32 as f64;
+
+/// Model dumped by [`Booster::dump_model_typed`](crate::Booster::dump_model_typed).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelDump {
+    pub name: String,
+    pub version: String,
+    pub num_class: usize,
+    pub num_tree_per_iteration: usize,
+    pub label_index: usize,
+    pub max_feature_idx: usize,
+
+    /// Objective and its parameters such as `"binary sigmoid:1"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective: Option<String>,
+    #[serde(default)]
+    pub average_output: bool,
+    pub feature_names: Vec<String>,
+    #[serde(default)]
+    pub monotone_constraints: Vec<i8>,
+
+    /// Value ranges of the features keyed by feature name.
+    #[serde(default)]
+    pub feature_infos: BTreeMap<String, FeatureInfo>,
+    pub tree_info: Vec<Tree>,
+    #[serde(default)]
+    pub feature_importances: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub pandas_categorical: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureInfo {
+    pub min_value: f64,
+    pub max_value: f64,
+
+    /// Categories of a categorical feature. Empty for a numerical feature.
+    #[serde(default)]
+    pub values: Vec<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tree {
+    pub tree_index: usize,
+    pub num_leaves: usize,
+    pub num_cat: usize,
+    pub shrinkage: f64,
+    pub tree_structure: Node,
+}
+impl Tree {
+    /// Number of edges on the longest path from the root to a leaf.
+    pub fn depth(&self) -> usize {
+        self.tree_structure.depth()
+    }
+
+    /// Leaves in the order of depth-first traversal from the left child.
+    pub fn leaves(&self) -> Vec<&LeafNode> {
+        let mut leaves = Vec::new();
+        self.tree_structure.collect_leaves(&mut leaves);
+        leaves
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Node {
+    Split(Box<SplitNode>),
+    Leaf(LeafNode),
+}
+impl Node {
+    /// Number of edges on the longest path from this node to a leaf.
+    pub fn depth(&self) -> usize {
+        match self {
+            Node::Split(split) => 1 + split.left_child.depth().max(split.right_child.depth()),
+            Node::Leaf(_) => 0,
+        }
+    }
+    fn collect_leaves<'a>(&'a self, leaves: &mut Vec<&'a LeafNode>) {
+        match self {
+            Node::Split(split) => {
+                split.left_child.collect_leaves(leaves);
+                split.right_child.collect_leaves(leaves);
+            }
+            Node::Leaf(leaf) => leaves.push(leaf),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplitNode {
+    pub split_index: usize,
+    pub split_feature: usize,
+    pub split_gain: f64,
+    pub threshold: Threshold,
+    pub decision_type: DecisionType,
+    pub default_left: bool,
+    pub missing_type: MissingType,
+    pub internal_value: f64,
+    pub internal_weight: f64,
+    pub internal_count: usize,
+    pub left_child: Node,
+    pub right_child: Node,
+}
+impl SplitNode {
+    /// Returns `true` if a row with `value` for `split_feature` goes to the left child.
+    ///
+    /// Follows the same rules as LightGBM, including `default_left` and `missing_type`.
+    pub fn goes_left(&self, value: f64) -> bool {
+        match &self.threshold {
+            Threshold::Numerical(threshold) => {
+                let value = if value.is_nan() && self.missing_type != MissingType::NaN {
+                    0.0
+                } else {
+                    value
+                };
+                match self.missing_type {
+                    MissingType::Zero if (-ZERO_THRESHOLD..=ZERO_THRESHOLD).contains(&value) => {
+                        self.default_left
+                    }
+                    MissingType::NaN if value.is_nan() => self.default_left,
+                    _ => value <= *threshold,
+                }
+            }
+            Threshold::Categorical(categories) => {
+                !value.is_nan() && value as i32 >= 0 && categories.contains(&(value as i32))
+            }
+        }
+    }
+}
+
+/// Leaf of a tree.
+///
+/// A tree with a single leaf has only `leaf_value` (and the linear model, if any).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeafNode {
+    #[serde(default)]
+    pub leaf_index: usize,
+    pub leaf_value: f64,
+    #[serde(default)]
+    pub leaf_weight: f64,
+    #[serde(default)]
+    pub leaf_count: usize,
+
+    /// Constant term of the linear model. `None` unless `linear_tree` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_const: Option<f64>,
+
+    /// Features used by the linear model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaf_features: Vec<usize>,
+
+    /// Coefficients of `leaf_features`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaf_coeff: Vec<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DecisionType {
+    /// Numerical split. Go left if `value <= threshold`.
+    #[serde(rename = "<=")]
+    LessOrEqual,
+
+    /// Categorical split. Go left if the category is in the threshold.
+    #[serde(rename = "==")]
+    Equal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MissingType {
+    None,
+    Zero,
+    NaN,
+}
+
+/// Threshold of a split.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Threshold {
+    Numerical(f64),
+
+    /// Categories that go to the left child.
+    Categorical(Vec<i32>),
+}
+impl Serialize for Threshold {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Threshold::Numerical(value) => serializer.serialize_f64(*value),
+            Threshold::Categorical(values) => {
+                let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                serializer.serialize_str(&values.join("||"))
+            }
+        }
+    }
+}
+impl<'de> Deserialize<'de> for Threshold {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Numerical(f64),
+            Categorical(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Numerical(value) => Ok(Threshold::Numerical(value)),
+            Raw::Categorical(s) => Ok(Threshold::Categorical(
+                s.split("||")
+                    .map(|v| v.parse().map_err(de::Error::custom))
+                    .collect::<Result<_, _>>()?,
+            )),
+        }
+    }
+}
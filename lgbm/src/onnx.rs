@@ -0,0 +1,464 @@
+//! Export models to [ONNX](https://onnx.ai/)
+//!
+//! Trees are converted to `TreeEnsembleRegressor` or `TreeEnsembleClassifier` of the `ai.onnx.ml` domain.
+//!
+//! | objective                                  | operator                 | outputs                                |
+//! | ------------------------------------------ | ------------------------ | -------------------------------------- |
+//! | `binary`, `multiclass`, `multiclassova`    | `TreeEnsembleClassifier` | `label` (int64), `probabilities` (float) |
+//! | others                                     | `TreeEnsembleRegressor`  | `variable` (float)                     |
+//!
+//! The input is `input` of type `tensor(float)` with shape `[N, num_feature]`.
+//!
+//! Thresholds are rounded down to `float` so that `float` inputs follow the same paths as in LightGBM,
+//! but leaf values are also stored as `float`, so predictions may differ slightly.
+//! Values of categorical features must be integers.
+//! Linear trees are not supported.
+
+use crate::{
+    Error, Result,
+    convert_output::ConvertOutput,
+    model::{MissingType, ModelDump, Node, SplitNode, Threshold},
+};
+
+const IR_VERSION: i64 = 8;
+const OPSET_VERSION: i64 = 16;
+const ML_OPSET_VERSION: i64 = 3;
+const ML_DOMAIN: &str = "ai.onnx.ml";
+
+const INPUT_NAME: &str = "input";
+
+/// Convert `model` to the bytes of an ONNX `ModelProto`.
+pub fn export(model: &ModelDump) -> Result<Vec<u8>> {
+    let num_class = model.num_tree_per_iteration;
+    if num_class == 0 || !model.tree_info.len().is_multiple_of(num_class) {
+        return Err(Error::from_message(&format!(
+            "number of trees must be a multiple of {num_class}, but got {}",
+            model.tree_info.len()
+        )));
+    }
+    let objective = model.objective.as_deref().unwrap_or("");
+    let objective_name = objective.split(' ').next().unwrap_or_default();
+    let convert_output = if objective.is_empty() {
+        ConvertOutput::Identity
+    } else {
+        ConvertOutput::parse(objective)?
+    };
+    let is_classifier = matches!(objective_name, "binary" | "multiclass" | "multiclassova");
+
+    let mut scale = 1.0;
+    if model.average_output {
+        scale /= (model.tree_info.len() / num_class).max(1) as f64;
+    }
+    // `LOGISTIC` has no parameter, so `sigmoid` is folded into the leaf values.
+    // This is valid for `multiclassova` because `LOGISTIC` is applied to each class independently.
+    if let ConvertOutput::Sigmoid(sigmoid) = convert_output
+        && is_classifier
+    {
+        scale *= sigmoid;
+    }
+    let mut ensemble = TreeEnsemble::default();
+    for (index, tree) in model.tree_info.iter().enumerate() {
+        ensemble.tree(index, index % num_class, &tree.tree_structure, scale)?;
+    }
+
+    let num_feature = model.max_feature_idx + 1;
+    let mut graph = Message::new();
+    let inputs = vec![value_info(
+        INPUT_NAME,
+        TensorType::Float,
+        &[Dim::Param("N"), Dim::Value(num_feature)],
+    )];
+    let mut outputs = Vec::new();
+    let mut nodes = Vec::new();
+    if is_classifier {
+        let post_transform = match convert_output {
+            ConvertOutput::Softmax => "SOFTMAX",
+            _ => "LOGISTIC",
+        };
+        let num_label = if num_class == 1 { 2 } else { num_class };
+        let mut node = ensemble.to_node("TreeEnsembleClassifier", "class", post_transform);
+        node.attribute_ints(
+            "classlabels_int64s",
+            &(0..num_label as i64).collect::<Vec<_>>(),
+        );
+        node.inputs(&[INPUT_NAME]);
+        node.outputs(&["label", "probabilities"]);
+        nodes.push(node);
+        outputs.push(value_info("label", TensorType::Int64, &[Dim::Param("N")]));
+        outputs.push(value_info(
+            "probabilities",
+            TensorType::Float,
+            &[Dim::Param("N"), Dim::Value(num_label)],
+        ));
+    } else {
+        let mut node = ensemble.to_node("TreeEnsembleRegressor", "target", "NONE");
+        node.attribute_int("n_targets", num_class as i64);
+        node.attribute_string("aggregate_function", "SUM");
+        node.inputs(&[INPUT_NAME]);
+        let shape = [Dim::Param("N"), Dim::Value(num_class)];
+        let raw = match convert_output {
+            ConvertOutput::Identity => "variable",
+            _ => "raw_score",
+        };
+        node.outputs(&[raw]);
+        nodes.push(node);
+        match convert_output {
+            ConvertOutput::Identity => {}
+            ConvertOutput::Exp => nodes.push(op("Exp", &[raw], &["variable"])),
+            ConvertOutput::LogExp => nodes.push(op("Softplus", &[raw], &["variable"])),
+            ConvertOutput::Sqrt => {
+                // sign(x) * x * x = x * |x|
+                nodes.push(op("Abs", &[raw], &["raw_score_abs"]));
+                nodes.push(op("Mul", &[raw, "raw_score_abs"], &["variable"]));
+            }
+            ConvertOutput::Sigmoid(1.0) => nodes.push(op("Sigmoid", &[raw], &["variable"])),
+            ConvertOutput::Sigmoid(_) | ConvertOutput::Softmax => {
+                return Err(Error::from_message(&format!(
+                    "unsupported objective: {objective}"
+                )));
+            }
+        }
+        outputs.push(value_info("variable", TensorType::Float, &shape));
+    }
+    for node in nodes {
+        graph.message(1, node.0);
+    }
+    graph.string(2, "lgbm");
+    for input in inputs {
+        graph.message(11, input);
+    }
+    for output in outputs {
+        graph.message(12, output);
+    }
+
+    let mut m = Message::new();
+    m.int(1, IR_VERSION);
+    m.string(2, "lgbm");
+    m.string(3, env!("CARGO_PKG_VERSION"));
+    m.message(7, graph);
+    m.message(8, opset("", OPSET_VERSION));
+    m.message(8, opset(ML_DOMAIN, ML_OPSET_VERSION));
+    Ok(m.0)
+}
+
+/// Convert the model of `booster` to the bytes of an ONNX `ModelProto`.
+#[cfg(feature = "lightgbm")]
+pub fn export_from_booster(booster: &crate::Booster) -> Result<Vec<u8>> {
+    let model = booster.dump_model_typed(0, None, crate::FeatureImportanceType::Split)?;
+    export(&model)
+}
+
+/// Attributes of `TreeEnsembleRegressor` and `TreeEnsembleClassifier`.
+#[derive(Default)]
+struct TreeEnsemble {
+    nodes_treeids: Vec<i64>,
+    nodes_nodeids: Vec<i64>,
+    nodes_featureids: Vec<i64>,
+    nodes_modes: Vec<&'static str>,
+    nodes_values: Vec<f32>,
+    nodes_truenodeids: Vec<i64>,
+    nodes_falsenodeids: Vec<i64>,
+    nodes_missing_value_tracks_true: Vec<i64>,
+    target_treeids: Vec<i64>,
+    target_nodeids: Vec<i64>,
+    target_ids: Vec<i64>,
+    target_weights: Vec<f32>,
+}
+
+impl TreeEnsemble {
+    fn tree(&mut self, tree_id: usize, target_id: usize, root: &Node, scale: f64) -> Result<()> {
+        let mut next_id = 0;
+        self.node(tree_id as i64, target_id as i64, root, scale, &mut next_id)?;
+        Ok(())
+    }
+
+    /// Add `node` and its descendants, and return the id of `node`.
+    fn node(
+        &mut self,
+        tree_id: i64,
+        target_id: i64,
+        node: &Node,
+        scale: f64,
+        next_id: &mut i64,
+    ) -> Result<i64> {
+        match node {
+            Node::Leaf(leaf) => {
+                if leaf.leaf_const.is_some() {
+                    return Err(Error::from_message("linear trees are not supported"));
+                }
+                let id = self.push(tree_id, next_id, "LEAF", 0, 0.0, false);
+                self.target_treeids.push(tree_id);
+                self.target_nodeids.push(id);
+                self.target_ids.push(target_id);
+                self.target_weights.push((leaf.leaf_value * scale) as f32);
+                Ok(id)
+            }
+            Node::Split(split) => {
+                let ids = self.split_nodes(tree_id, next_id, split)?;
+                let left = self.node(tree_id, target_id, &split.left_child, scale, next_id)?;
+                let right = self.node(tree_id, target_id, &split.right_child, scale, next_id)?;
+                for (i, &(idx, goes_left)) in ids.iter().enumerate() {
+                    let (true_id, false_id) = match ids.get(i + 1) {
+                        Some(&(next, _)) if goes_left => (left, self.nodes_nodeids[next]),
+                        Some(&(next, _)) => (right, self.nodes_nodeids[next]),
+                        None => (left, right),
+                    };
+                    self.nodes_truenodeids[idx] = true_id;
+                    self.nodes_falsenodeids[idx] = false_id;
+                }
+                Ok(self.nodes_nodeids[ids[0].0])
+            }
+        }
+    }
+
+    /// Add the nodes that represent `split`.
+    ///
+    /// Returns pairs of the index of each node and whether its true branch goes to the left child.
+    /// The false branch of each node goes to the next node, and the last node chooses between the left and right child.
+    fn split_nodes(
+        &mut self,
+        tree_id: i64,
+        next_id: &mut i64,
+        split: &SplitNode,
+    ) -> Result<Vec<(usize, bool)>> {
+        let feature = split.split_feature as i64;
+        let mut ids = Vec::new();
+        match &split.threshold {
+            Threshold::Numerical(threshold) => {
+                let threshold = *threshold;
+                let (zero_node, missing_left) = match split.missing_type {
+                    MissingType::None => (None, 0.0 <= threshold),
+                    MissingType::NaN => (None, split.default_left),
+                    MissingType::Zero => {
+                        // 0.0 goes to the default child, which `BRANCH_LEQ` alone may not do.
+                        let zero_left = 0.0 <= threshold;
+                        let zero_node =
+                            (zero_left != split.default_left).then_some(split.default_left);
+                        (zero_node, split.default_left)
+                    }
+                };
+                if let Some(goes_left) = zero_node {
+                    let idx = self.nodes_nodeids.len();
+                    self.push(tree_id, next_id, "BRANCH_EQ", feature, 0.0, true);
+                    ids.push((idx, goes_left));
+                }
+                let idx = self.nodes_nodeids.len();
+                self.push(
+                    tree_id,
+                    next_id,
+                    "BRANCH_LEQ",
+                    feature,
+                    f32_at_most(threshold),
+                    missing_left,
+                );
+                ids.push((idx, true));
+            }
+            Threshold::Categorical(categories) => {
+                if categories.is_empty() {
+                    return Err(Error::from_message("categorical split has no categories"));
+                }
+                for &category in categories {
+                    let idx = self.nodes_nodeids.len();
+                    self.push(
+                        tree_id,
+                        next_id,
+                        "BRANCH_EQ",
+                        feature,
+                        category as f32,
+                        false,
+                    );
+                    ids.push((idx, true));
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    fn push(
+        &mut self,
+        tree_id: i64,
+        next_id: &mut i64,
+        mode: &'static str,
+        feature: i64,
+        value: f32,
+        missing_tracks_true: bool,
+    ) -> i64 {
+        let id = *next_id;
+        *next_id += 1;
+        self.nodes_treeids.push(tree_id);
+        self.nodes_nodeids.push(id);
+        self.nodes_featureids.push(feature);
+        self.nodes_modes.push(mode);
+        self.nodes_values.push(value);
+        self.nodes_truenodeids.push(0);
+        self.nodes_falsenodeids.push(0);
+        self.nodes_missing_value_tracks_true
+            .push(missing_tracks_true as i64);
+        id
+    }
+
+    fn to_node(&self, op_type: &str, target: &str, post_transform: &str) -> NodeBuilder {
+        let mut node = NodeBuilder::new(op_type, ML_DOMAIN);
+        node.attribute_ints("nodes_treeids", &self.nodes_treeids);
+        node.attribute_ints("nodes_nodeids", &self.nodes_nodeids);
+        node.attribute_ints("nodes_featureids", &self.nodes_featureids);
+        node.attribute_strings("nodes_modes", &self.nodes_modes);
+        node.attribute_floats("nodes_values", &self.nodes_values);
+        node.attribute_ints("nodes_truenodeids", &self.nodes_truenodeids);
+        node.attribute_ints("nodes_falsenodeids", &self.nodes_falsenodeids);
+        node.attribute_ints(
+            "nodes_missing_value_tracks_true",
+            &self.nodes_missing_value_tracks_true,
+        );
+        node.attribute_ints(&format!("{target}_treeids"), &self.target_treeids);
+        node.attribute_ints(&format!("{target}_nodeids"), &self.target_nodeids);
+        node.attribute_ints(&format!("{target}_ids"), &self.target_ids);
+        node.attribute_floats(&format!("{target}_weights"), &self.target_weights);
+        node.attribute_string("post_transform", post_transform);
+        node
+    }
+}
+
+/// Largest `f32` not greater than `value`, so that `x <= value` and `x <= f32_at_most(value)` agree for any `f32` x.
+fn f32_at_most(value: f64) -> f32 {
+    let f = value as f32;
+    if f as f64 <= value {
+        f
+    } else if f == 0.0 {
+        -f32::from_bits(1)
+    } else if f > 0.0 {
+        f32::from_bits(f.to_bits() - 1)
+    } else {
+        f32::from_bits(f.to_bits() + 1)
+    }
+}
+
+fn op(op_type: &str, inputs: &[&str], outputs: &[&str]) -> NodeBuilder {
+    let mut node = NodeBuilder::new(op_type, "");
+    node.inputs(inputs);
+    node.outputs(outputs);
+    node
+}
+
+fn opset(domain: &str, version: i64) -> Message {
+    let mut m = Message::new();
+    m.string(1, domain);
+    m.int(2, version);
+    m
+}
+
+#[derive(Clone, Copy)]
+enum TensorType {
+    Float = 1,
+    Int64 = 7,
+}
+
+enum Dim<'a> {
+    Value(usize),
+    Param(&'a str),
+}
+
+fn value_info(name: &str, elem_type: TensorType, shape: &[Dim]) -> Message {
+    let mut shape_proto = Message::new();
+    for dim in shape {
+        let mut d = Message::new();
+        match dim {
+            Dim::Value(value) => d.int(1, *value as i64),
+            Dim::Param(param) => d.string(2, param),
+        }
+        shape_proto.message(1, d);
+    }
+    let mut tensor = Message::new();
+    tensor.int(1, elem_type as i64);
+    tensor.message(2, shape_proto);
+    let mut ty = Message::new();
+    ty.message(1, tensor);
+    let mut m = Message::new();
+    m.string(1, name);
+    m.message(2, ty);
+    m
+}
+
+/// `NodeProto`
+struct NodeBuilder(Message);
+
+impl NodeBuilder {
+    fn new(op_type: &str, domain: &str) -> Self {
+        let mut m = Message::new();
+        m.string(4, op_type);
+        if !domain.is_empty() {
+            m.string(7, domain);
+        }
+        Self(m)
+    }
+    fn inputs(&mut self, inputs: &[&str]) {
+        for input in inputs {
+            self.0.string(1, input);
+        }
+    }
+    fn outputs(&mut self, outputs: &[&str]) {
+        for output in outputs {
+            self.0.string(2, output);
+        }
+    }
+    fn attribute(&mut self, name: &str, ty: i64, f: impl FnOnce(&mut Message)) {
+        let mut a = Message::new();
+        a.string(1, name);
+        a.int(20, ty);
+        f(&mut a);
+        self.0.message(5, a);
+    }
+    fn attribute_int(&mut self, name: &str, value: i64) {
+        self.attribute(name, 2, |a| a.int(3, value));
+    }
+    fn attribute_string(&mut self, name: &str, value: &str) {
+        self.attribute(name, 3, |a| a.string(4, value));
+    }
+    fn attribute_floats(&mut self, name: &str, values: &[f32]) {
+        self.attribute(name, 6, |a| values.iter().for_each(|&v| a.float(7, v)));
+    }
+    fn attribute_ints(&mut self, name: &str, values: &[i64]) {
+        self.attribute(name, 7, |a| values.iter().for_each(|&v| a.int(8, v)));
+    }
+    fn attribute_strings(&mut self, name: &str, values: &[&str]) {
+        self.attribute(name, 8, |a| values.iter().for_each(|v| a.string(9, v)));
+    }
+}
+
+/// Protocol Buffers message encoder.
+struct Message(Vec<u8>);
+
+impl Message {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+    fn key(&mut self, field: u32, wire_type: u32) {
+        self.varint(((field << 3) | wire_type) as u64);
+    }
+    fn int(&mut self, field: u32, value: i64) {
+        self.key(field, 0);
+        self.varint(value as u64);
+    }
+    fn float(&mut self, field: u32, value: f32) {
+        self.key(field, 5);
+        self.0.extend(value.to_le_bytes());
+    }
+    fn bytes(&mut self, field: u32, value: &[u8]) {
+        self.key(field, 2);
+        self.varint(value.len() as u64);
+        self.0.extend(value);
+    }
+    fn string(&mut self, field: u32, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+    fn message(&mut self, field: u32, value: Message) {
+        self.bytes(field, &value.0);
+    }
+}
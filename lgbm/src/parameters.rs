@@ -1,8 +1,11 @@
 //! <https://lightgbm.readthedocs.io/en/latest/Parameters.html>
 
-use crate::{Result, utils::to_cstring};
+#[cfg(feature = "lightgbm")]
+use crate::utils::to_cstring;
+use crate::{Result, convert_output::ConvertOutput};
 use parse_display::{Display, FromStr};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "lightgbm")]
 use std::ffi::CString;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -16,6 +19,30 @@ pub enum ParameterValue {
     Array(Vec<ParameterValue>),
 }
 
+impl ParameterValue {
+    fn from_json(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::None,
+            serde_json::Value::Bool(value) => Self::Bool(value),
+            serde_json::Value::Number(value) => {
+                // Check the type rather than the value so that `1.0` stays `Float`.
+                if value.is_i64() {
+                    Self::Int(value.as_i64().unwrap())
+                } else if value.is_u64() {
+                    Self::USize(value.as_u64().unwrap() as usize)
+                } else {
+                    Self::Float(value.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(value) => Self::String(value),
+            serde_json::Value::Array(values) => {
+                Self::Array(values.into_iter().map(Self::from_json).collect())
+            }
+            serde_json::Value::Object(_) => Self::String(value.to_string()),
+        }
+    }
+}
+
 impl std::fmt::Display for ParameterValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -98,6 +125,21 @@ impl Parameters {
     pub fn push(&mut self, key: impl Into<String>, value: impl Into<ParameterValue>) {
         self.0.push((key.into(), value.into()));
     }
+
+    /// Returns the last value for `key`.
+    pub fn get(&self, key: &str) -> Option<&ParameterValue> {
+        self.0.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Parse a JSON object such as the output of [`Booster::loaded_parameters`](crate::Booster::loaded_parameters).
+    pub fn from_json(json: &str) -> Result<Self> {
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)?;
+        Ok(Self(
+            map.into_iter()
+                .map(|(key, value)| (key, ParameterValue::from_json(value)))
+                .collect(),
+        ))
+    }
 }
 
 impl std::fmt::Display for Parameters {
@@ -112,6 +154,7 @@ impl std::fmt::Display for Parameters {
     }
 }
 
+#[cfg(feature = "lightgbm")]
 impl Parameters {
     pub fn to_cstring(&self) -> Result<CString> {
         to_cstring(&self.to_string())
@@ -154,6 +197,31 @@ pub enum Objective {
     RankXendcg,
     Custom,
 }
+impl Objective {
+    /// Convert a raw score to the prediction space by the link function of the objective.
+    ///
+    /// `sigmoid` and `reg_sqrt` are read from `parameters`, which should be the parameters used to train the model.
+    /// Returns `None` for [`Objective::Multiclass`], which requires the raw scores of all classes.
+    pub fn convert_output(self, raw_score: f64, parameters: &Parameters) -> Result<Option<f64>> {
+        let mut objective = self.to_string();
+        if let Some(sigmoid) = parameters.get("sigmoid") {
+            objective.push_str(&format!(" sigmoid:{sigmoid}"));
+        }
+        if parameters
+            .get("reg_sqrt")
+            .is_some_and(|value| value.to_string() == "true")
+        {
+            objective.push_str(" sqrt");
+        }
+        let convert_output = ConvertOutput::parse(&objective)?;
+        if convert_output == ConvertOutput::Softmax {
+            return Ok(None);
+        }
+        let mut values = [raw_score];
+        convert_output.apply(&mut values);
+        Ok(Some(values[0]))
+    }
+}
 impl From<Objective> for ParameterValue {
     fn from(value: Objective) -> Self {
         value.to_string().into()
@@ -0,0 +1,474 @@
+//! Export models to [PMML 4.4](https://dmg.org/pmml/v4-4-1/GeneralStructure.html)
+//!
+//! The model is written as a `MiningModel` whose `Segmentation` contains a `TreeModel` for each tree.
+//!
+//! - Regression: the trees are summed, and the output transformation of the objective is applied
+//!   by a `RegressionModel` chained after them.
+//! - Classification (`binary`, `multiclass`, `multiclassova`): the trees of each class are summed,
+//!   and a `RegressionModel` with `logit` or `softmax` normalization outputs the probabilities.
+//!   The probabilities of `multiclassova` are the sigmoid of each class and are not normalized.
+//!
+//! Missing values are routed with `missingValueStrategy="defaultChild"` in the same way as LightGBM.
+//! Values of categorical features must be integers.
+//! Linear trees are not supported.
+
+use crate::{
+    Error, Result,
+    convert_output::ConvertOutput,
+    model::{MissingType, ModelDump, Node, SplitNode, Threshold},
+};
+use std::fmt::Write;
+
+const TARGET_NAME: &str = "_target";
+
+/// Convert `model` to a PMML document.
+pub fn export(model: &ModelDump) -> Result<String> {
+    let num_class = model.num_tree_per_iteration;
+    if num_class == 0 || !model.tree_info.len().is_multiple_of(num_class) {
+        return Err(Error::from_message(&format!(
+            "number of trees must be a multiple of {num_class}, but got {}",
+            model.tree_info.len()
+        )));
+    }
+    let num_feature = model.max_feature_idx + 1;
+    if model.feature_names.len() != num_feature {
+        return Err(Error::from_message(&format!(
+            "number of feature names must be {num_feature}, but got {}",
+            model.feature_names.len()
+        )));
+    }
+    let objective = model.objective.as_deref().unwrap_or("");
+    let objective_name = objective.split(' ').next().unwrap_or_default();
+    let convert_output = if objective.is_empty() {
+        ConvertOutput::Identity
+    } else {
+        ConvertOutput::parse(objective)?
+    };
+    let is_classifier = matches!(objective_name, "binary" | "multiclass" | "multiclassova");
+    let num_label = if num_class == 1 { 2 } else { num_class };
+
+    let mut w = Writer::new(model);
+    w.line(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    w.open(r#"<PMML xmlns="http://www.dmg.org/PMML-4_4" version="4.4">"#);
+    w.open("<Header>");
+    w.line(&format!(
+        r#"<Application name="lgbm" version="{}"/>"#,
+        env!("CARGO_PKG_VERSION")
+    ));
+    w.close("</Header>");
+    w.data_dictionary(is_classifier.then_some(num_label));
+
+    let function_name = if is_classifier {
+        "classification"
+    } else {
+        "regression"
+    };
+    w.open(&format!(r#"<MiningModel functionName="{function_name}">"#));
+    w.mining_schema(true);
+    if is_classifier {
+        w.open("<Output>");
+        for label in 0..num_label {
+            w.line(&format!(
+                r#"<OutputField name="probability({label})" optype="continuous" dataType="double" feature="probability" value="{label}"/>"#
+            ));
+        }
+        w.close("</Output>");
+    }
+    let chained = is_classifier || convert_output != ConvertOutput::Identity;
+    if chained {
+        w.open(r#"<Segmentation multipleModelMethod="modelChain">"#);
+        for class in 0..num_class {
+            w.open(&format!(r#"<Segment id="{}">"#, class + 1));
+            w.line("<True/>");
+            w.open(r#"<MiningModel functionName="regression">"#);
+            w.mining_schema(false);
+            w.open("<Output>");
+            w.line(&format!(
+                r#"<OutputField name="{}" optype="continuous" dataType="double" feature="predictedValue" isFinalResult="false"/>"#,
+                raw_score_name(class, num_class)
+            ));
+            w.close("</Output>");
+            w.trees(class)?;
+            w.close("</MiningModel>");
+            w.close("</Segment>");
+        }
+        w.open(&format!(r#"<Segment id="{}">"#, num_class + 1));
+        w.line("<True/>");
+        if is_classifier {
+            w.classification(convert_output, num_class);
+        } else {
+            w.regression(convert_output);
+        }
+        w.close("</Segment>");
+        w.close("</Segmentation>");
+    } else {
+        w.trees(0)?;
+    }
+    w.close("</MiningModel>");
+    w.close("</PMML>");
+    Ok(w.s)
+}
+
+/// Convert the model of `booster` to a PMML document.
+///
+/// Feature names are taken from [`Booster::get_feature_names`](crate::Booster::get_feature_names).
+#[cfg(feature = "lightgbm")]
+pub fn export_from_booster(booster: &crate::Booster) -> Result<String> {
+    let mut model = booster.dump_model_typed(0, None, crate::FeatureImportanceType::Split)?;
+    model.feature_names = booster.get_feature_names()?;
+    export(&model)
+}
+
+fn raw_score_name(class: usize, num_class: usize) -> String {
+    if num_class == 1 {
+        "lgbmValue".to_string()
+    } else {
+        format!("lgbmValue({class})")
+    }
+}
+
+struct Writer<'a> {
+    model: &'a ModelDump,
+    s: String,
+    depth: usize,
+    next_node_id: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(model: &'a ModelDump) -> Self {
+        Self {
+            model,
+            s: String::new(),
+            depth: 0,
+            next_node_id: 0,
+        }
+    }
+    fn line(&mut self, line: &str) {
+        writeln!(self.s, "{}{line}", "  ".repeat(self.depth)).unwrap();
+    }
+    fn open(&mut self, line: &str) {
+        self.line(line);
+        self.depth += 1;
+    }
+    fn close(&mut self, line: &str) {
+        self.depth -= 1;
+        self.line(line);
+    }
+
+    fn categories(&self, feature: usize) -> Option<&'a [i64]> {
+        let name = &self.model.feature_names[feature];
+        let info = self.model.feature_infos.get(name)?;
+        (!info.values.is_empty()).then_some(info.values.as_slice())
+    }
+    fn data_dictionary(&mut self, num_label: Option<usize>) {
+        let model = self.model;
+        self.open(&format!(
+            r#"<DataDictionary numberOfFields="{}">"#,
+            model.feature_names.len() + 1
+        ));
+        for (feature, name) in model.feature_names.iter().enumerate() {
+            let name = escape(name);
+            if let Some(categories) = self.categories(feature) {
+                self.open(&format!(
+                    r#"<DataField name="{name}" optype="categorical" dataType="integer">"#
+                ));
+                for category in categories {
+                    self.line(&format!(r#"<Value value="{category}"/>"#));
+                }
+                self.close("</DataField>");
+            } else {
+                self.line(&format!(
+                    r#"<DataField name="{name}" optype="continuous" dataType="double"/>"#
+                ));
+            }
+        }
+        if let Some(num_label) = num_label {
+            self.open(&format!(
+                r#"<DataField name="{TARGET_NAME}" optype="categorical" dataType="integer">"#
+            ));
+            for label in 0..num_label {
+                self.line(&format!(r#"<Value value="{label}"/>"#));
+            }
+            self.close("</DataField>");
+        } else {
+            self.line(&format!(
+                r#"<DataField name="{TARGET_NAME}" optype="continuous" dataType="double"/>"#
+            ));
+        }
+        self.close("</DataDictionary>");
+    }
+    fn mining_schema(&mut self, target: bool) {
+        let model = self.model;
+        self.open("<MiningSchema>");
+        if target {
+            self.line(&format!(
+                r#"<MiningField name="{TARGET_NAME}" usageType="target"/>"#
+            ));
+        }
+        for (feature, name) in model.feature_names.iter().enumerate() {
+            let name = escape(name);
+            if self.categories(feature).is_some() {
+                self.line(&format!(
+                    r#"<MiningField name="{name}" invalidValueTreatment="asIs"/>"#
+                ));
+            } else {
+                self.line(&format!(r#"<MiningField name="{name}"/>"#));
+            }
+        }
+        self.close("</MiningSchema>");
+    }
+
+    /// Write a `Segmentation` that sums the trees of `class`.
+    fn trees(&mut self, class: usize) -> Result<()> {
+        let model = self.model;
+        let method = if model.average_output {
+            "average"
+        } else {
+            "sum"
+        };
+        self.open(&format!(r#"<Segmentation multipleModelMethod="{method}">"#));
+        let trees = model
+            .tree_info
+            .iter()
+            .skip(class)
+            .step_by(model.num_tree_per_iteration);
+        for (index, tree) in trees.enumerate() {
+            self.open(&format!(r#"<Segment id="{}">"#, index + 1));
+            self.line("<True/>");
+            self.open(
+                r#"<TreeModel functionName="regression" missingValueStrategy="defaultChild" splitCharacteristic="binarySplit">"#,
+            );
+            self.mining_schema(false);
+            self.next_node_id = 0;
+            self.node(&tree.tree_structure, "<True/>")?;
+            self.close("</TreeModel>");
+            self.close("</Segment>");
+        }
+        self.close("</Segmentation>");
+        Ok(())
+    }
+    fn node(&mut self, node: &Node, predicate: &str) -> Result<()> {
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+        match node {
+            Node::Leaf(leaf) => {
+                if leaf.leaf_const.is_some() {
+                    return Err(Error::from_message("linear trees are not supported"));
+                }
+                self.open(&format!(
+                    r#"<Node id="{id}" score="{:?}">"#,
+                    leaf.leaf_value
+                ));
+                self.line(predicate);
+                self.close("</Node>");
+            }
+            Node::Split(split) => {
+                // The left child is written first, so its id is `id + 1`.
+                let left_id = id + 1;
+                let right_id = left_id + count_nodes(&split.left_child);
+                let default_left = self.missing_goes_left(split);
+                let default_child = if default_left { left_id } else { right_id };
+                self.open(&format!(
+                    r#"<Node id="{id}" defaultChild="{default_child}">"#
+                ));
+                self.line(predicate);
+                let left_predicate = self.left_predicate(split)?;
+                self.node(&split.left_child, &left_predicate)?;
+                self.node(&split.right_child, "<True/>")?;
+                self.close("</Node>");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a missing value goes to the left child.
+    fn missing_goes_left(&self, split: &SplitNode) -> bool {
+        match (&split.threshold, split.missing_type) {
+            (Threshold::Categorical(_), _) => false,
+            // NaN is treated as 0.0.
+            (Threshold::Numerical(threshold), MissingType::None) => 0.0 <= *threshold,
+            (Threshold::Numerical(_), MissingType::Zero | MissingType::NaN) => split.default_left,
+        }
+    }
+
+    /// Predicate of the left child for non-missing values.
+    fn left_predicate(&self, split: &SplitNode) -> Result<String> {
+        let field = escape(
+            self.model
+                .feature_names
+                .get(split.split_feature)
+                .ok_or_else(|| {
+                    Error::from_message(&format!("invalid feature index: {}", split.split_feature))
+                })?,
+        );
+        Ok(match &split.threshold {
+            Threshold::Numerical(threshold) => {
+                let le = format!(
+                    r#"<SimplePredicate field="{field}" operator="lessOrEqual" value="{threshold:?}"/>"#
+                );
+                let zero_left = 0.0 <= *threshold;
+                match split.missing_type {
+                    // 0.0 goes to the default child.
+                    MissingType::Zero if split.default_left && !zero_left => format!(
+                        r#"<CompoundPredicate booleanOperator="or">{le}<SimplePredicate field="{field}" operator="equal" value="0"/></CompoundPredicate>"#
+                    ),
+                    MissingType::Zero if !split.default_left && zero_left => format!(
+                        r#"<CompoundPredicate booleanOperator="and">{le}<SimplePredicate field="{field}" operator="notEqual" value="0"/></CompoundPredicate>"#
+                    ),
+                    _ => le,
+                }
+            }
+            Threshold::Categorical(categories) => {
+                let values = categories
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!(
+                    r#"<SimpleSetPredicate field="{field}" booleanOperator="isIn"><Array type="int" n="{}">{values}</Array></SimpleSetPredicate>"#,
+                    categories.len()
+                )
+            }
+        })
+    }
+
+    /// Write a `RegressionModel` that applies `convert_output` to the raw score.
+    fn regression(&mut self, convert_output: ConvertOutput) {
+        let x = r#"<FieldRef field="lgbmValue"/>"#;
+        let expr = match convert_output {
+            ConvertOutput::Identity => x.to_string(),
+            ConvertOutput::Exp => apply("exp", &[x]),
+            // sign(x) * x * x = x * |x|
+            ConvertOutput::Sqrt => apply("*", &[x, &apply("abs", &[x])]),
+            ConvertOutput::LogExp => {
+                apply("ln", &[&apply("+", &[&constant(1.0), &apply("exp", &[x])])])
+            }
+            ConvertOutput::Sigmoid(sigmoid) => sigmoid_expr(x, sigmoid),
+            ConvertOutput::Softmax => unreachable!("softmax is only used for classification"),
+        };
+        self.open(r#"<RegressionModel functionName="regression">"#);
+        self.open("<MiningSchema>");
+        self.line(&format!(
+            r#"<MiningField name="{TARGET_NAME}" usageType="target"/>"#
+        ));
+        self.line(r#"<MiningField name="lgbmValue"/>"#);
+        self.close("</MiningSchema>");
+        self.open("<LocalTransformations>");
+        self.open(
+            r#"<DerivedField name="transformedLgbmValue" optype="continuous" dataType="double">"#,
+        );
+        self.line(&expr);
+        self.close("</DerivedField>");
+        self.close("</LocalTransformations>");
+        self.open(r#"<RegressionTable intercept="0">"#);
+        self.line(r#"<NumericPredictor name="transformedLgbmValue" coefficient="1"/>"#);
+        self.close("</RegressionTable>");
+        self.close("</RegressionModel>");
+    }
+
+    /// Write a `RegressionModel` that converts the raw scores to probabilities.
+    ///
+    /// `logit` normalization is defined only for two categories,
+    /// so the sigmoid of each class of `multiclassova` is computed by a `DerivedField` and is not normalized.
+    fn classification(&mut self, convert_output: ConvertOutput, num_class: usize) {
+        let ova = num_class > 1 && matches!(convert_output, ConvertOutput::Sigmoid(_));
+        let (normalization, coefficient) = match convert_output {
+            ConvertOutput::Softmax => ("softmax", 1.0),
+            ConvertOutput::Sigmoid(_) if ova => ("none", 1.0),
+            ConvertOutput::Sigmoid(sigmoid) => ("logit", sigmoid),
+            _ => ("none", 1.0),
+        };
+        self.open(&format!(
+            r#"<RegressionModel functionName="classification" normalizationMethod="{normalization}">"#
+        ));
+        self.open("<MiningSchema>");
+        self.line(&format!(
+            r#"<MiningField name="{TARGET_NAME}" usageType="target"/>"#
+        ));
+        for class in 0..num_class {
+            self.line(&format!(
+                r#"<MiningField name="{}"/>"#,
+                raw_score_name(class, num_class)
+            ));
+        }
+        self.close("</MiningSchema>");
+        if ova && let ConvertOutput::Sigmoid(sigmoid) = convert_output {
+            self.open("<LocalTransformations>");
+            for class in 0..num_class {
+                self.open(&format!(
+                    r#"<DerivedField name="sigmoidLgbmValue({class})" optype="continuous" dataType="double">"#
+                ));
+                let x = format!(r#"<FieldRef field="lgbmValue({class})"/>"#);
+                self.line(&sigmoid_expr(&x, sigmoid));
+                self.close("</DerivedField>");
+            }
+            self.close("</LocalTransformations>");
+        }
+        if num_class == 1 {
+            // With `logit`, the probability of the second category is 1 - p.
+            self.open(r#"<RegressionTable intercept="0" targetCategory="1">"#);
+            self.line(&format!(
+                r#"<NumericPredictor name="lgbmValue" coefficient="{coefficient:?}"/>"#
+            ));
+            self.close("</RegressionTable>");
+            self.line(r#"<RegressionTable intercept="0" targetCategory="0"/>"#);
+        } else {
+            let input = if ova { "sigmoidLgbmValue" } else { "lgbmValue" };
+            for class in 0..num_class {
+                self.open(&format!(
+                    r#"<RegressionTable intercept="0" targetCategory="{class}">"#
+                ));
+                self.line(&format!(
+                    r#"<NumericPredictor name="{input}({class})" coefficient="{coefficient:?}"/>"#
+                ));
+                self.close("</RegressionTable>");
+            }
+        }
+        self.close("</RegressionModel>");
+    }
+}
+
+fn count_nodes(node: &Node) -> usize {
+    match node {
+        Node::Split(split) => 1 + count_nodes(&split.left_child) + count_nodes(&split.right_child),
+        Node::Leaf(_) => 1,
+    }
+}
+
+/// `1 / (1 + exp(-sigmoid * x))`
+fn sigmoid_expr(x: &str, sigmoid: f64) -> String {
+    apply(
+        "/",
+        &[
+            &constant(1.0),
+            &apply(
+                "+",
+                &[
+                    &constant(1.0),
+                    &apply("exp", &[&apply("*", &[&constant(-sigmoid), x])]),
+                ],
+            ),
+        ],
+    )
+}
+fn apply(function: &str, args: &[&str]) -> String {
+    format!(r#"<Apply function="{function}">{}</Apply>"#, args.concat())
+}
+fn constant(value: f64) -> String {
+    format!(r#"<Constant dataType="double">{value:?}</Constant>"#)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
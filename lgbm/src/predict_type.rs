@@ -0,0 +1,9 @@
+/// Discriminants are the `C_API_PREDICT_*` constants of LightGBM.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PredictType {
+    Normal = 0,
+    RawScore = 1,
+    LeafIndex = 2,
+    Contrib = 3,
+}
@@ -0,0 +1,172 @@
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use text_grid::{Cells, cells_f, cells_schema, to_grid_with_schema};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Prediction {
+    pub(crate) num: [usize; 3],
+    pub(crate) values: Vec<f64>,
+}
+impl Prediction {
+    pub(crate) fn new(num_data: usize, num_class: usize, num_2: usize) -> Self {
+        Self {
+            values: vec![f64::NAN; num_data * num_class * num_2],
+            num: [num_data, num_class, num_2],
+        }
+    }
+    pub(crate) fn from_num_predict(
+        num_predict: usize,
+        num_data: usize,
+        num_class: usize,
+    ) -> Result<Self> {
+        if num_data * num_class == 0 {
+            return Ok(Self::new(num_data, num_class, 1));
+        }
+        let num_2 = num_predict / num_class / num_data;
+        if num_data * num_class * num_2 != num_predict {
+            return Err(Error::from_message("invalid num_data"));
+        }
+        Ok(Self::new(num_data, num_class, num_2))
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+    pub fn num_data(&self) -> usize {
+        self.num[0]
+    }
+    pub fn num_class(&self) -> usize {
+        self.num[1]
+    }
+    pub fn num_iteration(&self) -> usize {
+        self.num[2]
+    }
+    pub fn num_feature(&self) -> usize {
+        self.num[2] - 1
+    }
+
+    fn fmt_with<T: Cells>(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        to_cells: impl Fn(f64) -> T,
+    ) -> std::fmt::Result {
+        writeln!(f, "num_data  : {}", self.num_data())?;
+        writeln!(f, "num_class : {}", self.num_class())?;
+        writeln!(f, "num_2     : {}", self.num[2])?;
+        self.fmt_values_with(f, to_cells)
+    }
+    fn fmt_values_with<T: Cells>(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        to_cells: impl Fn(f64) -> T,
+    ) -> std::fmt::Result {
+        writeln!(f)?;
+        match (self.num_class(), self.num[2]) {
+            (_, 1) => {
+                let schema = cells_schema(|f| {
+                    f.column("", |&row| row);
+                    for column in 0..self.num_class() {
+                        f.column(column, |&row| to_cells(self[[row, column]]));
+                    }
+                });
+                writeln!(f, "{}", to_grid_with_schema(0..self.num_data(), schema))?;
+            }
+            (_, _) => {
+                writeln!(f, "{:?}", &self.values)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn approx_eq(&self, other: &Self, margin: f64) -> bool {
+        self.values.len() == other.values.len()
+            && self
+                .values
+                .iter()
+                .zip(other.values.iter())
+                .all(|(&v0, &v1)| approx_eq(v0, v1, margin))
+    }
+}
+
+fn approx_eq(v0: f64, v1: f64, margin: f64) -> bool {
+    // `v0 == v1` to return true for the same infinity.
+    v0 == v1 || (v0 - v1).abs() <= margin
+}
+
+impl std::fmt::Display for Prediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let p = f.precision();
+        if let Some(p) = p {
+            self.fmt_with(f, |x| cells_f!("{:.*}", p, x))
+        } else {
+            self.fmt_with(f, |x| cells_f!("{:}", x))
+        }
+    }
+}
+impl std::fmt::LowerExp for Prediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let p = f.precision();
+        if let Some(p) = p {
+            self.fmt_with(f, |x| cells_f!("{:.*e}", p, x))
+        } else {
+            self.fmt_with(f, |x| cells_f!("{:e}", x))
+        }
+    }
+}
+impl std::fmt::UpperExp for Prediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let p = f.precision();
+        if let Some(p) = p {
+            self.fmt_with(f, |x| cells_f!("{:.*E}", p, x))
+        } else {
+            self.fmt_with(f, |x| cells_f!("{:E}", x))
+        }
+    }
+}
+impl std::fmt::Debug for Prediction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        struct Values<'a>(&'a Prediction);
+        impl std::fmt::Debug for Values<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let p = f.precision();
+                if let Some(p) = p {
+                    self.0.fmt_values_with(f, |x| cells_f!("{:.*?}", p, x))
+                } else {
+                    self.0.fmt_values_with(f, |x| cells_f!("{:?}", x))
+                }
+            }
+        }
+        f.debug_struct("Prediction")
+            .field("num", &self.num)
+            .field("values", &Values(self))
+            .finish()
+    }
+}
+
+impl std::ops::Index<usize> for Prediction {
+    type Output = f64;
+    fn index(&self, data: usize) -> &f64 {
+        assert_eq!(self.num_class(), 1, "num_class");
+        assert_eq!(self.num[2], 1, "num_2");
+        &self.values[data]
+    }
+}
+impl std::ops::Index<[usize; 2]> for Prediction {
+    type Output = f64;
+    fn index(&self, [data, class]: [usize; 2]) -> &f64 {
+        assert_eq!(self.num[2], 1, "num_2");
+        &self.values[data * self.num_class() + class]
+    }
+}
+impl std::ops::Index<[usize; 3]> for Prediction {
+    type Output = f64;
+    fn index(&self, [data, class, iteration]: [usize; 3]) -> &f64 {
+        &self.values[data * self.num_class() * self.num[2] + class * self.num[2] + iteration]
+    }
+}
+
+impl From<Prediction> for Vec<f64> {
+    fn from(p: Prediction) -> Self {
+        p.values
+    }
+}
@@ -0,0 +1,467 @@
+//! Pure-Rust predictor for LightGBM text models
+//!
+//! [`Predictor`] parses the text produced by `Booster::save_model_to_string` and predicts without `lib_lightgbm`.
+
+use crate::{Error, PredictType, Result, convert_output::ConvertOutput, model::ZERO_THRESHOLD};
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+const CATEGORICAL_MASK: u8 = 1;
+const DEFAULT_LEFT_MASK: u8 = 2;
+
+const MISSING_TYPE_ZERO: u8 = 1;
+const MISSING_TYPE_NAN: u8 = 2;
+
+/// Model loaded from the LightGBM text model format.
+#[derive(Clone, Debug)]
+pub struct Predictor {
+    num_class: usize,
+    num_tree_per_iteration: usize,
+    num_feature: usize,
+    average_output: bool,
+    convert_output: ConvertOutput,
+    feature_names: Vec<String>,
+    trees: Vec<Tree>,
+}
+
+impl Predictor {
+    /// Load a model from the text produced by `Booster::save_model_to_string`.
+    pub fn from_string(model: &str) -> Result<Self> {
+        let mut lines = model.lines().map(|line| line.trim_end_matches('\r'));
+        let mut header = HashMap::new();
+        let mut average_output = false;
+        let mut trees = Vec::new();
+        let mut tree = None;
+        for line in lines.by_ref() {
+            if line == "end of trees" {
+                break;
+            }
+            if line.starts_with("Tree=") {
+                if let Some(tree) = tree.take() {
+                    trees.push(Tree::parse(&tree)?);
+                }
+                tree = Some(HashMap::new());
+                continue;
+            }
+            if let Some(tree) = &mut tree {
+                if let Some((key, value)) = line.split_once('=') {
+                    tree.insert(key, value);
+                }
+            } else if let Some((key, value)) = line.split_once('=') {
+                header.insert(key, value);
+            } else if line == "average_output" {
+                average_output = true;
+            }
+        }
+        if let Some(tree) = tree.take() {
+            trees.push(Tree::parse(&tree)?);
+        }
+
+        let num_class = parse_value(&header, "num_class")?;
+        let num_tree_per_iteration = parse_value(&header, "num_tree_per_iteration")?;
+        let max_feature_idx: usize = parse_value(&header, "max_feature_idx")?;
+        if num_tree_per_iteration == 0 {
+            return Err(Error::from_message("num_tree_per_iteration must not be 0"));
+        }
+        if !trees.len().is_multiple_of(num_tree_per_iteration) {
+            return Err(Error::from_message(&format!(
+                "number of trees must be a multiple of {num_tree_per_iteration}, but got {}",
+                trees.len()
+            )));
+        }
+        let num_feature = max_feature_idx + 1;
+        for tree in &trees {
+            tree.check_features(num_feature)?;
+        }
+        let convert_output = match header.get("objective") {
+            Some(objective) => ConvertOutput::parse(objective)?,
+            None => ConvertOutput::Identity,
+        };
+        let feature_names = header
+            .get("feature_names")
+            .map(|names| names.split(' ').map(|s| s.to_string()).collect())
+            .unwrap_or_default();
+        Ok(Self {
+            num_class,
+            num_tree_per_iteration,
+            num_feature,
+            average_output,
+            convert_output,
+            feature_names,
+            trees,
+        })
+    }
+
+    /// Load a model from a file saved by `Booster::save_model`.
+    pub fn from_file(filename: &Path) -> Result<Self> {
+        Self::from_string(&std::fs::read_to_string(filename).map_err(Error::from_error)?)
+    }
+
+    pub fn num_feature(&self) -> usize {
+        self.num_feature
+    }
+    pub fn num_class(&self) -> usize {
+        self.num_class
+    }
+    pub fn num_iteration(&self) -> usize {
+        self.trees.len() / self.num_tree_per_iteration
+    }
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Number of values predicted for each row.
+    pub fn num_predict(
+        &self,
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> Result<usize> {
+        match predict_type {
+            PredictType::Normal | PredictType::RawScore => Ok(self.num_class),
+            PredictType::LeafIndex => {
+                let iterations = self.iterations(start_iteration, num_iteration);
+                Ok(iterations.len() * self.num_tree_per_iteration)
+            }
+            PredictType::Contrib => Err(Error::from_message(
+                "PredictType::Contrib is not supported by Predictor",
+            )),
+        }
+    }
+
+    /// Predict rows stored in row-major order in `data`.
+    ///
+    /// The result has the same layout as `Booster::predict_for_mat`.
+    pub fn predict<T: Copy + Into<f64>>(
+        &self,
+        data: &[T],
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> Result<Vec<f64>> {
+        if !data.len().is_multiple_of(self.num_feature) {
+            return Err(Error::from_message(&format!(
+                "data length must be a multiple of {}, but got {}",
+                self.num_feature,
+                data.len()
+            )));
+        }
+        let num_row = data.len() / self.num_feature;
+        let num_predict = self.num_predict(predict_type, start_iteration, num_iteration)?;
+        let mut out = vec![0.0; num_row * num_predict];
+        if num_predict == 0 {
+            return Ok(out);
+        }
+        for (row, out) in data
+            .chunks(self.num_feature)
+            .zip(out.chunks_mut(num_predict))
+        {
+            self.predict_row(row, predict_type, start_iteration, num_iteration, out)?;
+        }
+        Ok(out)
+    }
+
+    /// Predict a single row.
+    ///
+    /// `out` must have [`num_predict`](Self::num_predict) elements.
+    pub fn predict_row<T: Copy + Into<f64>>(
+        &self,
+        row: &[T],
+        predict_type: PredictType,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+        out: &mut [f64],
+    ) -> Result<()> {
+        if row.len() != self.num_feature {
+            return Err(Error::from_message(&format!(
+                "row length must be {}, but got {}",
+                self.num_feature,
+                row.len()
+            )));
+        }
+        let num_predict = self.num_predict(predict_type, start_iteration, num_iteration)?;
+        if out.len() != num_predict {
+            return Err(Error::from_message(&format!(
+                "out length must be {num_predict}, but got {}",
+                out.len()
+            )));
+        }
+        let features: Vec<f64> = row
+            .iter()
+            .map(|&x| {
+                let x = x.into();
+                if x.abs() > ZERO_THRESHOLD || x.is_nan() {
+                    x
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let iterations = self.iterations(start_iteration, num_iteration);
+        let trees = &self.trees[iterations.start * self.num_tree_per_iteration
+            ..iterations.end * self.num_tree_per_iteration];
+        if predict_type == PredictType::LeafIndex {
+            for (tree, out) in trees.iter().zip(out) {
+                *out = tree.leaf(&features) as f64;
+            }
+            return Ok(());
+        }
+        out.fill(0.0);
+        for trees in trees.chunks(self.num_tree_per_iteration) {
+            for (tree, out) in trees.iter().zip(out.iter_mut()) {
+                *out += tree.predict(&features);
+            }
+        }
+        if self.average_output && !iterations.is_empty() {
+            for out in out.iter_mut() {
+                *out /= iterations.len() as f64;
+            }
+        }
+        if predict_type == PredictType::Normal {
+            self.convert_output.apply(out);
+        }
+        Ok(())
+    }
+
+    fn iterations(
+        &self,
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> std::ops::Range<usize> {
+        let total = self.num_iteration();
+        let start = start_iteration.min(total);
+        let end = match num_iteration {
+            Some(n) if n > 0 => start + n.min(total - start),
+            _ => total,
+        };
+        start..end
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Tree {
+    split_feature: Vec<usize>,
+    threshold: Vec<f64>,
+    decision_type: Vec<u8>,
+    left_child: Vec<i32>,
+    right_child: Vec<i32>,
+    leaf_value: Vec<f64>,
+    cat_boundaries: Vec<usize>,
+    cat_threshold: Vec<u32>,
+    linear: Option<LinearLeaves>,
+}
+
+#[derive(Clone, Debug)]
+struct LinearLeaves {
+    leaf_const: Vec<f64>,
+    leaf_features: Vec<Vec<usize>>,
+    leaf_coeff: Vec<Vec<f64>>,
+}
+
+impl Tree {
+    fn parse(values: &HashMap<&str, &str>) -> Result<Self> {
+        let num_leaves: usize = parse_value(values, "num_leaves")?;
+        let num_cat: usize = parse_value(values, "num_cat").unwrap_or(0);
+        let num_split = num_leaves.saturating_sub(1);
+        let leaf_value = parse_array(values, "leaf_value", num_leaves)?;
+        let (split_feature, threshold, decision_type, left_child, right_child) = if num_split > 0 {
+            (
+                parse_array(values, "split_feature", num_split)?,
+                parse_array(values, "threshold", num_split)?,
+                parse_array::<i8>(values, "decision_type", num_split)?
+                    .into_iter()
+                    .map(|x| x as u8)
+                    .collect(),
+                parse_array(values, "left_child", num_split)?,
+                parse_array(values, "right_child", num_split)?,
+            )
+        } else {
+            Default::default()
+        };
+        let (cat_boundaries, cat_threshold) = if num_cat > 0 {
+            let cat_boundaries: Vec<usize> = parse_array(values, "cat_boundaries", num_cat + 1)?;
+            let len = cat_boundaries[num_cat];
+            (cat_boundaries, parse_array(values, "cat_threshold", len)?)
+        } else {
+            Default::default()
+        };
+        let is_linear = parse_value::<i32>(values, "is_linear").unwrap_or(0) != 0;
+        let linear = if is_linear {
+            let leaf_const = parse_array(values, "leaf_const", num_leaves)?;
+            let num_features: Vec<usize> = parse_array(values, "num_features", num_leaves)?;
+            let total = num_features.iter().sum();
+            let features: Vec<usize> = parse_array(values, "leaf_features", total)?;
+            let coeff: Vec<f64> = parse_array(values, "leaf_coeff", total)?;
+            let mut leaf_features = Vec::new();
+            let mut leaf_coeff = Vec::new();
+            let mut offset = 0;
+            for n in num_features {
+                leaf_features.push(features[offset..offset + n].to_vec());
+                leaf_coeff.push(coeff[offset..offset + n].to_vec());
+                offset += n;
+            }
+            Some(LinearLeaves {
+                leaf_const,
+                leaf_features,
+                leaf_coeff,
+            })
+        } else {
+            None
+        };
+        let tree = Self {
+            split_feature,
+            threshold,
+            decision_type,
+            left_child,
+            right_child,
+            leaf_value,
+            cat_boundaries,
+            cat_threshold,
+            linear,
+        };
+        tree.check_nodes(num_cat)?;
+        Ok(tree)
+    }
+
+    fn check_nodes(&self, num_cat: usize) -> Result<()> {
+        let num_split = self.split_feature.len() as i32;
+        let num_leaves = self.leaf_value.len() as i32;
+        for &child in self.left_child.iter().chain(&self.right_child) {
+            if child >= num_split || !child >= num_leaves {
+                return Err(Error::from_message(&format!("invalid child node: {child}")));
+            }
+        }
+        for (node, &decision_type) in self.decision_type.iter().enumerate() {
+            if decision_type & CATEGORICAL_MASK != 0 {
+                let cat_idx = self.threshold[node] as usize;
+                if cat_idx >= num_cat {
+                    return Err(Error::from_message(&format!(
+                        "invalid categorical split: {cat_idx}"
+                    )));
+                }
+            }
+        }
+        for w in self.cat_boundaries.windows(2) {
+            if w[0] > w[1] || w[1] > self.cat_threshold.len() {
+                return Err(Error::from_message("invalid cat_boundaries"));
+            }
+        }
+        Ok(())
+    }
+    fn check_features(&self, num_feature: usize) -> Result<()> {
+        let linear_features = self
+            .linear
+            .iter()
+            .flat_map(|l| l.leaf_features.iter().flatten());
+        for &feature in self.split_feature.iter().chain(linear_features) {
+            if feature >= num_feature {
+                return Err(Error::from_message(&format!(
+                    "feature index must be less than {num_feature}, but got {feature}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn leaf(&self, features: &[f64]) -> usize {
+        if self.split_feature.is_empty() {
+            return 0;
+        }
+        let mut node = 0;
+        while node >= 0 {
+            let n = node as usize;
+            let value = features[self.split_feature[n]];
+            node = if self.decision_type[n] & CATEGORICAL_MASK != 0 {
+                self.categorical_decision(value, n)
+            } else {
+                self.numerical_decision(value, n)
+            };
+        }
+        !node as usize
+    }
+    fn numerical_decision(&self, mut value: f64, node: usize) -> i32 {
+        let decision_type = self.decision_type[node];
+        let missing_type = (decision_type >> 2) & 3;
+        if value.is_nan() && missing_type != MISSING_TYPE_NAN {
+            value = 0.0;
+        }
+        if (missing_type == MISSING_TYPE_ZERO
+            && (-ZERO_THRESHOLD..=ZERO_THRESHOLD).contains(&value))
+            || (missing_type == MISSING_TYPE_NAN && value.is_nan())
+        {
+            if decision_type & DEFAULT_LEFT_MASK != 0 {
+                return self.left_child[node];
+            } else {
+                return self.right_child[node];
+            }
+        }
+        if value <= self.threshold[node] {
+            self.left_child[node]
+        } else {
+            self.right_child[node]
+        }
+    }
+    fn categorical_decision(&self, value: f64, node: usize) -> i32 {
+        if value.is_nan() || (value as i32) < 0 {
+            return self.right_child[node];
+        }
+        let category = value as i32 as usize;
+        let cat_idx = self.threshold[node] as usize;
+        let bits =
+            &self.cat_threshold[self.cat_boundaries[cat_idx]..self.cat_boundaries[cat_idx + 1]];
+        if bits
+            .get(category / 32)
+            .is_some_and(|&b| (b >> (category % 32)) & 1 != 0)
+        {
+            self.left_child[node]
+        } else {
+            self.right_child[node]
+        }
+    }
+
+    fn predict(&self, features: &[f64]) -> f64 {
+        let leaf = self.leaf(features);
+        if let Some(linear) = &self.linear {
+            let mut output = linear.leaf_const[leaf];
+            for (&feature, &coeff) in linear.leaf_features[leaf]
+                .iter()
+                .zip(&linear.leaf_coeff[leaf])
+            {
+                let value = features[feature];
+                if value.is_nan() {
+                    return self.leaf_value[leaf];
+                }
+                output += coeff * value;
+            }
+            output
+        } else {
+            self.leaf_value[leaf]
+        }
+    }
+}
+
+fn parse<T: FromStr>(s: &str) -> Result<T> {
+    s.parse()
+        .map_err(|_| Error::from_message(&format!("failed to parse `{s}`")))
+}
+fn parse_value<T: FromStr>(values: &HashMap<&str, &str>, key: &str) -> Result<T> {
+    let value = values
+        .get(key)
+        .ok_or_else(|| Error::from_message(&format!("`{key}` not found")))?;
+    parse(value)
+}
+fn parse_array<T: FromStr>(values: &HashMap<&str, &str>, key: &str, len: usize) -> Result<Vec<T>> {
+    let value = values
+        .get(key)
+        .ok_or_else(|| Error::from_message(&format!("`{key}` not found")))?;
+    let values = value
+        .split_whitespace()
+        .map(parse)
+        .collect::<Result<Vec<T>>>()?;
+    if values.len() != len {
+        return Err(Error::from_message(&format!(
+            "length of `{key}` must be {len}, but got {}",
+            values.len()
+        )));
+    }
+    Ok(values)
+}
@@ -0,0 +1,369 @@
+use crate::{
+    Error, Prediction, Result,
+    model::{ModelDump, Node, Tree, ZERO_THRESHOLD},
+};
+use std::ops::Range;
+
+impl ModelDump {
+    /// Number of iterations in [`tree_info`](Self::tree_info).
+    pub fn num_iteration(&self) -> usize {
+        self.tree_info.len() / self.num_tree_per_iteration.max(1)
+    }
+
+    /// Compute SHAP values with TreeSHAP.
+    ///
+    /// `data` contains rows of `max_feature_idx + 1` features in row-major order.
+    /// The result has the same layout as `Booster::predict_for_mat` with `PredictType::Contrib`:
+    /// `num_feature + 1` values for each row and class, where the last one is the expected value.
+    pub fn shap_values<T: Copy + Into<f64>>(
+        &self,
+        data: &[T],
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> Result<Prediction> {
+        let num_column = self.max_feature_idx + 2;
+        let mut out = Vec::new();
+        for (row, trees) in self.shap_rows(data, start_iteration, num_iteration)? {
+            for trees in trees {
+                let mut phi = vec![0.0; num_column];
+                for tree in trees {
+                    tree_shap(tree, &row, &mut phi, Condition::None);
+                }
+                out.extend(phi);
+            }
+        }
+        self.to_prediction(data.len(), out)
+    }
+
+    /// Compute SHAP interaction values with TreeSHAP.
+    ///
+    /// The result contains a `(num_feature + 1) x (num_feature + 1)` row-major matrix for each row and class,
+    /// so the element `(i, j)` is at `[row, class, i * (num_feature + 1) + j]`.
+    /// Off-diagonal elements are the interaction effects, diagonal elements are the main effects,
+    /// and the last diagonal element is the expected value.
+    /// Each row of a matrix sums to the corresponding value of [`shap_values`](Self::shap_values).
+    pub fn shap_interaction_values<T: Copy + Into<f64>>(
+        &self,
+        data: &[T],
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> Result<Prediction> {
+        let num_column = self.max_feature_idx + 2;
+        let mut out = Vec::new();
+        for (row, trees) in self.shap_rows(data, start_iteration, num_iteration)? {
+            for trees in trees {
+                let mut phi = vec![0.0; num_column];
+                for tree in &trees {
+                    tree_shap(tree, &row, &mut phi, Condition::None);
+                }
+                let mut matrix = vec![0.0; num_column * num_column];
+                for i in 0..num_column {
+                    let mut phi_on = vec![0.0; num_column];
+                    let mut phi_off = vec![0.0; num_column];
+                    for tree in &trees {
+                        tree_shap(tree, &row, &mut phi_on, Condition::On(i));
+                        tree_shap(tree, &row, &mut phi_off, Condition::Off(i));
+                    }
+                    let m = &mut matrix[i * num_column..(i + 1) * num_column];
+                    m[i] = phi[i];
+                    for j in 0..num_column {
+                        if j != i {
+                            m[j] = (phi_on[j] - phi_off[j]) / 2.0;
+                            m[i] -= m[j];
+                        }
+                    }
+                }
+                out.extend(matrix);
+            }
+        }
+        self.to_prediction(data.len(), out)
+    }
+
+    /// Wrap the values computed for `data` of `data_len` elements.
+    fn to_prediction(&self, data_len: usize, values: Vec<f64>) -> Result<Prediction> {
+        let num_data = data_len / (self.max_feature_idx + 1);
+        let mut p =
+            Prediction::from_num_predict(values.len(), num_data, self.num_tree_per_iteration)?;
+        p.values = values;
+        Ok(p)
+    }
+
+    /// Returns the normalized rows and, for each row, the trees of each class.
+    fn shap_rows<T: Copy + Into<f64>>(
+        &self,
+        data: &[T],
+        start_iteration: usize,
+        num_iteration: Option<usize>,
+    ) -> Result<impl Iterator<Item = (Vec<f64>, Vec<Vec<&Tree>>)>> {
+        let num_feature = self.max_feature_idx + 1;
+        if !data.len().is_multiple_of(num_feature) {
+            return Err(Error::from_message(&format!(
+                "data length must be a multiple of {num_feature}, but got {}",
+                data.len()
+            )));
+        }
+        if self
+            .tree_info
+            .iter()
+            .any(|tree| is_linear(&tree.tree_structure))
+        {
+            return Err(Error::from_message(
+                "SHAP values of linear trees are not supported",
+            ));
+        }
+        let iterations = self.iterations(start_iteration, num_iteration);
+        let num_tree_per_iteration = self.num_tree_per_iteration;
+        let trees = &self.tree_info
+            [iterations.start * num_tree_per_iteration..iterations.end * num_tree_per_iteration];
+        Ok(data.chunks(num_feature).map(move |row| {
+            let row = row
+                .iter()
+                .map(|&x| {
+                    let x = x.into();
+                    if x.abs() > ZERO_THRESHOLD || x.is_nan() {
+                        x
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+            let trees = (0..num_tree_per_iteration)
+                .map(|k| {
+                    trees
+                        .iter()
+                        .skip(k)
+                        .step_by(num_tree_per_iteration)
+                        .collect()
+                })
+                .collect();
+            (row, trees)
+        }))
+    }
+    fn iterations(&self, start_iteration: usize, num_iteration: Option<usize>) -> Range<usize> {
+        let total = self.num_iteration();
+        let start = start_iteration.min(total);
+        let end = match num_iteration {
+            Some(n) if n > 0 => start + n.min(total - start),
+            _ => total,
+        };
+        start..end
+    }
+}
+
+fn is_linear(node: &Node) -> bool {
+    match node {
+        Node::Split(split) => is_linear(&split.left_child) || is_linear(&split.right_child),
+        Node::Leaf(leaf) => leaf.leaf_const.is_some(),
+    }
+}
+
+/// Feature whose presence is fixed while computing SHAP interaction values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Condition {
+    None,
+    On(usize),
+    Off(usize),
+}
+impl Condition {
+    fn feature(self) -> Option<usize> {
+        match self {
+            Condition::None => None,
+            Condition::On(feature) | Condition::Off(feature) => Some(feature),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PathElement {
+    feature_index: Option<usize>,
+    zero_fraction: f64,
+    one_fraction: f64,
+    pweight: f64,
+}
+
+fn tree_shap(tree: &Tree, row: &[f64], phi: &mut [f64], condition: Condition) {
+    let root = &tree.tree_structure;
+    if condition == Condition::None {
+        let bias = phi.len() - 1;
+        phi[bias] += expected_value(tree);
+    }
+    if let Node::Split(_) = root {
+        recurse(root, row, phi, &[], 1.0, 1.0, None, condition, 1.0);
+    }
+}
+
+fn expected_value(tree: &Tree) -> f64 {
+    let root = &tree.tree_structure;
+    let mut leaves = tree.leaves();
+    if leaves.len() == 1 {
+        return leaves[0].leaf_value;
+    }
+    leaves.sort_by_key(|leaf| leaf.leaf_index);
+    let total_count = data_count(root);
+    leaves
+        .iter()
+        .map(|leaf| (leaf.leaf_count as f64 / total_count) * leaf.leaf_value)
+        .fold(0.0, |acc, x| acc + x)
+}
+
+fn data_count(node: &Node) -> f64 {
+    match node {
+        Node::Split(split) => split.internal_count as f64,
+        Node::Leaf(leaf) => leaf.leaf_count as f64,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn recurse(
+    node: &Node,
+    row: &[f64],
+    phi: &mut [f64],
+    parent_path: &[PathElement],
+    parent_zero_fraction: f64,
+    parent_one_fraction: f64,
+    parent_feature_index: Option<usize>,
+    condition: Condition,
+    condition_fraction: f64,
+) {
+    if condition_fraction == 0.0 {
+        return;
+    }
+    let mut path = parent_path.to_vec();
+    if condition.feature().is_none() || condition.feature() != parent_feature_index {
+        extend_path(
+            &mut path,
+            parent_zero_fraction,
+            parent_one_fraction,
+            parent_feature_index,
+        );
+    }
+    match node {
+        Node::Leaf(leaf) => {
+            for i in 1..path.len() {
+                let w = unwound_path_sum(&path, i);
+                let el = &path[i];
+                if let Some(feature_index) = el.feature_index {
+                    phi[feature_index] += w
+                        * (el.one_fraction - el.zero_fraction)
+                        * leaf.leaf_value
+                        * condition_fraction;
+                }
+            }
+        }
+        Node::Split(split) => {
+            let feature = split.split_feature;
+            let (hot, cold) = if split.goes_left(row[feature]) {
+                (&split.left_child, &split.right_child)
+            } else {
+                (&split.right_child, &split.left_child)
+            };
+            let w = data_count(node);
+            let hot_zero_fraction = data_count(hot) / w;
+            let cold_zero_fraction = data_count(cold) / w;
+            let mut incoming_zero_fraction = 1.0;
+            let mut incoming_one_fraction = 1.0;
+
+            if let Some(path_index) = path.iter().position(|e| e.feature_index == Some(feature)) {
+                incoming_zero_fraction = path[path_index].zero_fraction;
+                incoming_one_fraction = path[path_index].one_fraction;
+                unwind_path(&mut path, path_index);
+            }
+
+            let mut hot_condition_fraction = condition_fraction;
+            let mut cold_condition_fraction = condition_fraction;
+            match condition {
+                Condition::On(f) if f == feature => cold_condition_fraction = 0.0,
+                Condition::Off(f) if f == feature => {
+                    hot_condition_fraction *= hot_zero_fraction;
+                    cold_condition_fraction *= cold_zero_fraction;
+                }
+                _ => {}
+            }
+            recurse(
+                hot,
+                row,
+                phi,
+                &path,
+                hot_zero_fraction * incoming_zero_fraction,
+                incoming_one_fraction,
+                Some(feature),
+                condition,
+                hot_condition_fraction,
+            );
+            recurse(
+                cold,
+                row,
+                phi,
+                &path,
+                cold_zero_fraction * incoming_zero_fraction,
+                0.0,
+                Some(feature),
+                condition,
+                cold_condition_fraction,
+            );
+        }
+    }
+}
+
+fn extend_path(
+    path: &mut Vec<PathElement>,
+    zero_fraction: f64,
+    one_fraction: f64,
+    feature_index: Option<usize>,
+) {
+    let depth = path.len();
+    path.push(PathElement {
+        feature_index,
+        zero_fraction,
+        one_fraction,
+        pweight: if depth == 0 { 1.0 } else { 0.0 },
+    });
+    for i in (0..depth).rev() {
+        path[i + 1].pweight += one_fraction * path[i].pweight * (i + 1) as f64 / (depth + 1) as f64;
+        path[i].pweight = zero_fraction * path[i].pweight * (depth - i) as f64 / (depth + 1) as f64;
+    }
+}
+
+fn unwind_path(path: &mut Vec<PathElement>, path_index: usize) {
+    let depth = path.len() - 1;
+    let one_fraction = path[path_index].one_fraction;
+    let zero_fraction = path[path_index].zero_fraction;
+    let mut next_one_portion = path[depth].pweight;
+    for i in (0..depth).rev() {
+        if one_fraction != 0.0 {
+            let tmp = path[i].pweight;
+            path[i].pweight =
+                next_one_portion * (depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            next_one_portion =
+                tmp - path[i].pweight * zero_fraction * (depth - i) as f64 / (depth + 1) as f64;
+        } else {
+            path[i].pweight =
+                (path[i].pweight * (depth + 1) as f64) / (zero_fraction * (depth - i) as f64);
+        }
+    }
+    for i in path_index..depth {
+        path[i].feature_index = path[i + 1].feature_index;
+        path[i].zero_fraction = path[i + 1].zero_fraction;
+        path[i].one_fraction = path[i + 1].one_fraction;
+    }
+    path.pop();
+}
+
+fn unwound_path_sum(path: &[PathElement], path_index: usize) -> f64 {
+    let depth = path.len() - 1;
+    let one_fraction = path[path_index].one_fraction;
+    let zero_fraction = path[path_index].zero_fraction;
+    let mut next_one_portion = path[depth].pweight;
+    let mut total = 0.0;
+    for i in (0..depth).rev() {
+        if one_fraction != 0.0 {
+            let tmp = next_one_portion * (depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            total += tmp;
+            next_one_portion =
+                path[i].pweight - tmp * zero_fraction * ((depth - i) as f64 / (depth + 1) as f64);
+        } else if zero_fraction != 0.0 {
+            total += (path[i].pweight / zero_fraction) / ((depth - i) as f64 / (depth + 1) as f64);
+        }
+    }
+    total
+}
@@ -0,0 +1,379 @@
+//! Training loop with validation, early stopping and callbacks
+
+use crate::{Booster, Dataset, Error, EvalResult, Field, Parameters, Prediction, Result};
+use std::{collections::BTreeMap, io::Write, ops::ControlFlow, sync::Arc};
+
+/// Custom objective function.
+pub trait Objective {
+    /// Compute the gradients and hessians of the loss for the training dataset.
+    ///
+    /// `preds` contains the raw scores of the current model, indexed by `preds[[row, class]]`.
+    ///
+    /// `grad` and `hess` have `num_class * num_data` elements in class-major order.
+    /// The value for `row` of `class` must be stored at `class * num_data + row`.
+    /// Unlike `preds`, they are passed to [`Booster::update_one_iter_custom`] as is,
+    /// so they use the layout of `LGBM_BoosterUpdateOneIterCustom`.
+    fn grad_hess(&self, preds: &Prediction, dataset: &Dataset, grad: &mut [f32], hess: &mut [f32]);
+}
+
+/// Fields of a dataset passed to [`EvalMetric`].
+#[derive(Clone, Copy, Debug)]
+pub struct EvalData<'a> {
+    pub label: &'a [f32],
+    pub weight: Option<&'a [f32]>,
+
+    /// Query boundaries.
+    pub group: Option<&'a [i32]>,
+}
+impl<'a> EvalData<'a> {
+    pub fn new(dataset: &'a Dataset) -> Result<Self> {
+        let weight = dataset.get_field(Field::WEIGHT)?;
+        let group = dataset.get_field(Field::GROUP)?;
+        Ok(Self {
+            label: dataset.get_field(Field::LABEL)?,
+            weight: (!weight.is_empty()).then_some(weight),
+            group: (!group.is_empty()).then_some(group),
+        })
+    }
+}
+
+/// Custom evaluation metric.
+pub trait EvalMetric {
+    fn name(&self) -> &str;
+    fn higher_better(&self) -> bool;
+
+    /// Evaluate the scores of [`Booster::get_predict`] for a dataset.
+    ///
+    /// `preds` is indexed by `preds[[row, class]]`, the same as [`Objective::grad_hess`].
+    /// `preds` are raw scores if a custom objective is used.
+    fn eval(&self, preds: &Prediction, data: &EvalData) -> f64;
+}
+
+/// User-defined functions used by [`train_custom`].
+#[derive(Clone, Copy, Default)]
+pub struct Custom<'a> {
+    /// Objective used instead of the `objective` parameter.
+    pub objective: Option<&'a dyn Objective>,
+
+    /// Metrics evaluated in addition to the `metric` parameter.
+    pub metrics: &'a [&'a dyn EvalMetric],
+}
+
+/// Evaluation history keyed by dataset name and metric name.
+pub type EvalHistory = BTreeMap<String, BTreeMap<String, Vec<f64>>>;
+
+/// State of the training loop passed to [`Callback`].
+pub struct CallbackEnv<'a> {
+    pub booster: &'a mut Booster,
+
+    /// Index of the current iteration starting from 0.
+    pub iteration: usize,
+    pub num_boost_round: usize,
+
+    /// Evaluations of the current iteration. Empty in [`Callback::before_iteration`].
+    pub evals: &'a [EvalResult],
+    pub history: &'a EvalHistory,
+
+    /// Number of iterations of the best model.
+    pub best_iteration: &'a mut Option<usize>,
+}
+
+/// Hook called by [`train`] for each iteration.
+pub trait Callback {
+    /// Called before each iteration.
+    ///
+    /// Parameters can be changed here by [`Booster::reset_parameter`].
+    fn before_iteration(&mut self, env: &mut CallbackEnv) -> Result<()> {
+        let _ = env;
+        Ok(())
+    }
+
+    /// Called after each iteration. Return [`ControlFlow::Break`] to stop training.
+    fn after_iteration(&mut self, env: &mut CallbackEnv) -> Result<ControlFlow<()>> {
+        let _ = env;
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// Stop training when a validation metric has not improved for `patience` iterations.
+///
+/// Metrics on the training dataset are ignored.
+#[derive(Clone, Debug)]
+pub struct EarlyStopping {
+    pub patience: usize,
+
+    /// Minimum change of a metric to be considered as an improvement.
+    pub min_delta: f64,
+
+    /// Use only the first metric for early stopping.
+    pub first_metric_only: bool,
+
+    best: Vec<(f64, usize)>,
+}
+impl EarlyStopping {
+    pub fn new(patience: usize) -> Self {
+        Self {
+            patience,
+            min_delta: 0.0,
+            first_metric_only: false,
+            best: Vec::new(),
+        }
+    }
+    pub fn min_delta(self, min_delta: f64) -> Self {
+        Self { min_delta, ..self }
+    }
+    pub fn first_metric_only(self, first_metric_only: bool) -> Self {
+        Self {
+            first_metric_only,
+            ..self
+        }
+    }
+}
+impl Callback for EarlyStopping {
+    fn after_iteration(&mut self, env: &mut CallbackEnv) -> Result<ControlFlow<()>> {
+        let Some(first_metric_name) = env.evals.first().map(|e| &e.metric_name) else {
+            return Ok(ControlFlow::Continue(()));
+        };
+        if self.best.len() != env.evals.len() {
+            self.best = vec![(f64::NAN, 0); env.evals.len()];
+        }
+        let mut result = ControlFlow::Continue(());
+        let mut best_iteration = None;
+        for (eval, (best_score, best_iter)) in env.evals.iter().zip(&mut self.best) {
+            if eval.data_idx == 0
+                || self.first_metric_only && &eval.metric_name != first_metric_name
+            {
+                continue;
+            }
+            let improved = if best_score.is_nan() {
+                true
+            } else if eval.higher_better {
+                eval.value - self.min_delta > *best_score
+            } else {
+                eval.value + self.min_delta < *best_score
+            };
+            if improved {
+                *best_score = eval.value;
+                *best_iter = env.iteration;
+            } else if env.iteration - *best_iter >= self.patience {
+                result = ControlFlow::Break(());
+            }
+            if best_iteration.is_none() || result.is_break() {
+                best_iteration = Some(*best_iter + 1);
+            }
+            if result.is_break() {
+                break;
+            }
+        }
+        if best_iteration.is_some() {
+            *env.best_iteration = best_iteration;
+        }
+        Ok(result)
+    }
+}
+
+/// Write evaluations every `period` iterations.
+///
+/// Evaluations are written to stderr unless another writer is set by [`writer`](Self::writer).
+pub struct LogEvaluation {
+    pub period: usize,
+    writer: Box<dyn Write>,
+}
+impl LogEvaluation {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            writer: Box::new(std::io::stderr()),
+        }
+    }
+    pub fn writer(self, writer: impl Write + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+            ..self
+        }
+    }
+}
+impl std::fmt::Debug for LogEvaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogEvaluation")
+            .field("period", &self.period)
+            .finish_non_exhaustive()
+    }
+}
+impl Callback for LogEvaluation {
+    fn after_iteration(&mut self, env: &mut CallbackEnv) -> Result<ControlFlow<()>> {
+        if self.period > 0
+            && !env.evals.is_empty()
+            && ((env.iteration + 1).is_multiple_of(self.period)
+                || env.iteration + 1 == env.num_boost_round)
+        {
+            let evals = env
+                .evals
+                .iter()
+                .map(|e| format!("{}'s {}: {}", e.data_name, e.metric_name, e.value))
+                .collect::<Vec<_>>();
+            writeln!(self.writer, "[{}]\t{}", env.iteration + 1, evals.join("\t"))
+                .map_err(Error::from_error)?;
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// Reset parameters before each iteration using a scheduler.
+///
+/// The scheduler receives the index of the iteration and returns the parameters to change.
+/// Nothing is changed if the returned parameters are empty.
+pub struct ResetParameter<F>(pub F);
+impl<F: FnMut(usize) -> Parameters> Callback for ResetParameter<F> {
+    fn before_iteration(&mut self, env: &mut CallbackEnv) -> Result<()> {
+        let parameters = (self.0)(env.iteration);
+        if !parameters.0.is_empty() {
+            env.booster.reset_parameter(&parameters)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct TrainResult {
+    pub booster: Booster,
+
+    /// Number of iterations of the best model determined by a callback such as [`EarlyStopping`].
+    pub best_iteration: Option<usize>,
+    pub history: EvalHistory,
+}
+
+/// Train a booster for `num_boost_round` iterations.
+///
+/// Each dataset in `valid` is evaluated after each iteration.
+/// If `train` itself is included in `valid`, the metrics on the training dataset are evaluated.
+///
+/// Similar to [`lightgbm.train`](https://lightgbm.readthedocs.io/en/latest/pythonapi/lightgbm.train.html) of the Python package.
+pub fn train(
+    parameters: &Parameters,
+    train: Arc<Dataset>,
+    valid: &[(&str, Arc<Dataset>)],
+    num_boost_round: usize,
+    callbacks: &mut [&mut dyn Callback],
+) -> Result<TrainResult> {
+    train_custom(
+        parameters,
+        train,
+        valid,
+        num_boost_round,
+        callbacks,
+        Custom::default(),
+    )
+}
+
+/// Train a booster using user-defined functions.
+///
+/// If `custom.objective` is specified, the `objective` parameter is overridden by
+/// [`Custom`](crate::parameters::Objective::Custom).
+pub fn train_custom(
+    parameters: &Parameters,
+    train: Arc<Dataset>,
+    valid: &[(&str, Arc<Dataset>)],
+    num_boost_round: usize,
+    callbacks: &mut [&mut dyn Callback],
+    custom: Custom,
+) -> Result<TrainResult> {
+    let mut booster = if custom.objective.is_some() {
+        let mut parameters = parameters.clone();
+        parameters.push("objective", crate::parameters::Objective::Custom);
+        Booster::new(train.clone(), &parameters)?
+    } else {
+        Booster::new(train.clone(), parameters)?
+    };
+    let mut data = Vec::new();
+    for (name, dataset) in valid {
+        let data_idx = if Arc::ptr_eq(dataset, &train) {
+            0
+        } else {
+            booster.add_valid_data(dataset.clone())?;
+            booster.data.len() - 1
+        };
+        data.push((data_idx, name.to_string(), dataset.clone()));
+    }
+    let mut history = EvalHistory::new();
+    let mut best_iteration = None;
+    for iteration in 0..num_boost_round {
+        for callback in callbacks.iter_mut() {
+            callback.before_iteration(&mut CallbackEnv {
+                booster: &mut booster,
+                iteration,
+                num_boost_round,
+                evals: &[],
+                history: &history,
+                best_iteration: &mut best_iteration,
+            })?;
+        }
+        let is_finished = if let Some(objective) = custom.objective {
+            booster.update_one_iter_objective(objective)?
+        } else {
+            booster.update_one_iter()?
+        };
+        let evals = evaluate(&booster, &data, custom.metrics)?;
+        for e in &evals {
+            history
+                .entry(e.data_name.clone())
+                .or_default()
+                .entry(e.metric_name.clone())
+                .or_default()
+                .push(e.value);
+        }
+        let mut is_break = is_finished;
+        for callback in callbacks.iter_mut() {
+            is_break |= callback
+                .after_iteration(&mut CallbackEnv {
+                    booster: &mut booster,
+                    iteration,
+                    num_boost_round,
+                    evals: &evals,
+                    history: &history,
+                    best_iteration: &mut best_iteration,
+                })?
+                .is_break();
+        }
+        if is_break {
+            break;
+        }
+    }
+    Ok(TrainResult {
+        booster,
+        best_iteration,
+        history,
+    })
+}
+
+fn evaluate(
+    booster: &Booster,
+    data: &[(usize, String, Arc<Dataset>)],
+    metrics: &[&dyn EvalMetric],
+) -> Result<Vec<EvalResult>> {
+    let mut evals = Vec::new();
+    if data.is_empty() {
+        return Ok(evals);
+    }
+    let metric_names = booster.get_eval_names()?;
+    for (data_idx, data_name, dataset) in data {
+        let values = booster.get_eval(*data_idx)?;
+        for (metric_name, value) in metric_names.iter().zip(values) {
+            evals.push(EvalResult::new(*data_idx, data_name, metric_name, value));
+        }
+        if !metrics.is_empty() {
+            let preds = booster.get_predict_by_row(*data_idx)?;
+            let eval_data = EvalData::new(dataset)?;
+            for metric in metrics {
+                evals.push(EvalResult {
+                    data_idx: *data_idx,
+                    data_name: data_name.clone(),
+                    metric_name: metric.name().to_string(),
+                    metric: None,
+                    value: metric.eval(&preds, &eval_data),
+                    higher_better: metric.higher_better(),
+                });
+            }
+        }
+    }
+    Ok(evals)
+}
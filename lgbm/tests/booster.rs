@@ -1,8 +1,9 @@
 use anyhow::Result;
 use lgbm::{
-    Booster, Dataset, FeatureImportanceType, Field, MatBuf, Parameters, PredictType,
-    mat::RowMajor,
-    parameters::{Boosting, DeviceType, Metric, Objective, Verbosity},
+    Booster, Dataset, FastCsrPredictor, FastPredictor, FeatureImportanceType, Field, MatBuf,
+    Parameters, PredictType, SparseMatBuf,
+    mat::{Csc, Csr, RowMajor},
+    parameters::{Boosting, DeviceType, Metric, Objective, ParameterValue, Verbosity},
 };
 use std::sync::Arc;
 
@@ -462,6 +463,546 @@ fn save_to_string() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn predict_for_csr() -> Result<()> {
+    let num_class = 2;
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let d = make_dataset(128, num_class, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    for _ in 0..10 {
+        if b.update_one_iter()? {
+            break;
+        }
+    }
+
+    let features = make_features(64, num_class);
+    let csr = SparseMatBuf::<f64, i32, Csr>::from_dense(&features)?;
+    for predict_type in [PredictType::Normal, PredictType::Contrib] {
+        let r0 = b.predict_for_mat(&features, predict_type, 0, None, &p)?;
+        let r1 = b.predict_for_csr(&csr, predict_type, 0, None, &p)?;
+        assert!(r0.approx_eq(&r1, 0.001));
+    }
+    Ok(())
+}
+
+#[test]
+fn predict_for_csc() -> Result<()> {
+    let num_class = 2;
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let d = make_dataset(128, num_class, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    for _ in 0..10 {
+        if b.update_one_iter()? {
+            break;
+        }
+    }
+
+    let features = make_features(64, num_class);
+    let csc = SparseMatBuf::<f64, i64, Csc>::from_dense(&features)?;
+    let r0 = b.predict_for_mat(&features, PredictType::Normal, 0, None, &p)?;
+    let r1 = b.predict_for_csc(&csc, PredictType::Normal, 0, None, &p)?;
+    assert!(r0.approx_eq(&r1, 0.001));
+    Ok(())
+}
+
+#[test]
+fn predict_sparse_output_csr() -> Result<()> {
+    let num_class = 3;
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", num_class);
+
+    let d = make_dataset(128, num_class, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    for _ in 0..10 {
+        if b.update_one_iter()? {
+            break;
+        }
+    }
+
+    let features = make_features(64, num_class);
+    let csr = SparseMatBuf::<f64, i32, Csr>::from_dense(&features)?;
+    let r0 = b.predict_for_mat(&features, PredictType::Contrib, 0, None, &p)?;
+    let r1 = b.predict_sparse_output(&csr, 0, None, &p)?;
+    assert_eq!(r1.num_class(), num_class);
+    assert_eq!(r1.num_data(), 64);
+    assert_eq!(r1.rows(0).count(), 64);
+    assert!(r0.approx_eq(&r1.to_prediction(), 0.001));
+    Ok(())
+}
+
+#[test]
+fn predict_sparse_output_csc() -> Result<()> {
+    let num_class = 2;
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let d = make_dataset(128, num_class, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    for _ in 0..10 {
+        if b.update_one_iter()? {
+            break;
+        }
+    }
+
+    let features = make_features(64, num_class);
+    let csc = SparseMatBuf::<f64, i64, Csc>::from_dense(&features)?;
+    let r0 = b.predict_for_mat(&features, PredictType::Contrib, 0, None, &p)?;
+    let r1 = b.predict_sparse_output(&csc, 0, None, &p)?;
+    assert_eq!(r1.num_class(), 1);
+    assert!(r0.approx_eq(&r1.to_prediction(), 0.001));
+    Ok(())
+}
+
+#[test]
+fn fast_predictor() -> Result<()> {
+    let num_class = 3;
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", num_class);
+
+    let d = make_dataset(128, num_class, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    for _ in 0..10 {
+        if b.update_one_iter()? {
+            break;
+        }
+    }
+
+    let features = make_features(16, num_class);
+    for predict_type in [PredictType::Normal, PredictType::RawScore] {
+        let expected = b.predict_for_mat(&features, predict_type, 0, None, &p)?;
+        let mut dense = FastPredictor::<f64>::new(&b, predict_type, 0, None, &p)?;
+        let mut sparse = FastCsrPredictor::<f64>::new(&b, predict_type, 0, None, &p)?;
+        assert_eq!(dense.num_predict(), num_class);
+        assert_eq!(sparse.num_predict(), num_class);
+
+        let mut out = vec![0.0; num_class];
+        for row in 0..features.nrow() {
+            dense.predict(features.row(row), &mut out)?;
+            for class in 0..num_class {
+                assert!((out[class] - expected[[row, class]]).abs() < 0.001);
+            }
+
+            let indices = [0, 1];
+            sparse.predict(&indices, features.row(row), &mut out)?;
+            for class in 0..num_class {
+                assert!((out[class] - expected[[row, class]]).abs() < 0.001);
+            }
+        }
+        assert!(dense.predict(&[1.0], &mut out).is_err());
+        assert!(dense.predict(features.row(0), &mut [0.0]).is_err());
+        assert!(sparse.predict(&[2], &[1.0], &mut out).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn predict_for_csr_invalid_num_feature() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let d = make_dataset(128, 2, None, &p)?;
+    let b = Booster::new(d, &p)?;
+    let csr = SparseMatBuf::<f64, i32, Csr>::from_vecs(vec![0, 1], vec![0], vec![1.0], 1, 3, Csr)?;
+    assert!(
+        b.predict_for_csr(&csr, PredictType::Normal, 0, None, &p)
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn eval_all() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", [Metric::BinaryLogloss, Metric::Auc]);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let valid = make_dataset(50, 2, Some(&train), &p)?;
+    let mut b = Booster::new(train, &p)?;
+    b.add_valid_data(valid)?;
+    b.update_one_iter()?;
+
+    let r = b.eval_all()?;
+    assert_eq!(r.len(), 4);
+    assert_eq!(r[0].data_idx, 0);
+    assert_eq!(r[0].data_name, "training");
+    assert_eq!(r[0].metric, Some(Metric::BinaryLogloss));
+    assert!(!r[0].higher_better);
+    assert_eq!(r[3].data_idx, 1);
+    assert_eq!(r[3].data_name, "valid_0");
+    assert_eq!(r[3].metric_name, "auc");
+    assert_eq!(r[3].metric, Some(Metric::Auc));
+    assert!(r[3].higher_better);
+    assert_eq!(r[3].value, b.get_eval(1)?[1]);
+    Ok(())
+}
+
+#[test]
+fn refit() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    for _ in 0..5 {
+        b.update_one_iter()?;
+    }
+    let features = make_features(100, 2);
+    let leaf = b.predict_for_mat(&features, PredictType::LeafIndex, 0, None, &p)?;
+    let leaf_preds = MatBuf::from_vec(
+        leaf.values().iter().map(|&x| x as i32).collect(),
+        100,
+        5,
+        RowMajor,
+    );
+    b.refit(&leaf_preds)?;
+    assert_eq!(b.number_of_total_model()?, 5);
+
+    let invalid = MatBuf::from_vec(vec![0; 100 * 4], 100, 4, RowMajor);
+    assert!(b.refit(&invalid).is_err());
+    let invalid = MatBuf::from_vec(vec![0; 99 * 5], 99, 5, RowMajor);
+    assert!(b.refit(&invalid).is_err());
+
+    b.refit_mat(make_features(50, 2), &make_labels(50, 2), &p)?;
+    assert_eq!(b.number_of_total_model()?, 5);
+    let leaf1 = b.predict_for_mat(&features, PredictType::LeafIndex, 0, None, &p)?;
+    assert_eq!(leaf.values(), leaf1.values());
+    Ok(())
+}
+
+#[test]
+fn from_init_model() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    for _ in 0..5 {
+        b.update_one_iter()?;
+    }
+    let m = b.save_model_to_string(0, None, FeatureImportanceType::Gain)?;
+    let (init_model, _) = Booster::from_string(&m)?;
+
+    let features = make_features(80, 2);
+    let mut train = Dataset::from_mat(&features, None, &p)?;
+    train.set_field(Field::LABEL, &make_labels(80, 2))?;
+    let mut b = Booster::from_init_model(&init_model, train, &features, &p)?;
+    assert_eq!(b.get_current_iteration()?, 5);
+    for _ in 0..3 {
+        b.update_one_iter()?;
+    }
+    assert_eq!(b.get_current_iteration()?, 8);
+    assert_eq!(b.number_of_total_model()?, 8);
+
+    let raw0 = init_model.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    let raw1 = b.predict_for_mat(&features, PredictType::RawScore, 0, Some(5), &p)?;
+    assert!(raw0.approx_eq(&raw1, 1e-6));
+    Ok(())
+}
+
+#[test]
+fn reset_training_data() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let train1 = make_dataset(50, 2, Some(&train), &p)?;
+    let mut b = Booster::new(train, &p)?;
+    b.update_one_iter()?;
+    b.reset_training_data(train1)?;
+    b.update_one_iter()?;
+    assert_eq!(b.get_current_iteration()?, 2);
+    assert_eq!(b.data(0).unwrap().get_num_data()?, 50);
+
+    let m = b.save_model_to_string(0, None, FeatureImportanceType::Gain)?;
+    let (mut b1, _) = Booster::from_string(&m)?;
+    assert!(
+        b1.reset_training_data(make_dataset(50, 2, None, &p)?)
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn loaded_parameters() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    p.push("metric", [Metric::MultiLogloss, Metric::MultiError]);
+    p.push("learning_rate", 0.05);
+
+    let train = make_dataset(90, 3, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    b.update_one_iter()?;
+    let m = b.save_model_to_string(0, None, FeatureImportanceType::Gain)?;
+    let (b1, _) = Booster::from_string(&m)?;
+
+    let p1 = b1.loaded_parameters()?;
+    assert_eq!(
+        p1.get("objective"),
+        Some(&ParameterValue::String("multiclass".into()))
+    );
+    assert_eq!(p1.get("num_class"), Some(&ParameterValue::Int(3)));
+    assert_eq!(p1.get("learning_rate"), Some(&ParameterValue::Float(0.05)));
+    assert_eq!(
+        p1.get("metric"),
+        Some(&ParameterValue::Array(vec![
+            ParameterValue::String("multi_logloss".into()),
+            ParameterValue::String("multi_error".into()),
+        ]))
+    );
+    Ok(())
+}
+
+#[test]
+fn parameters_from_json() -> Result<()> {
+    let p = Parameters::from_json(r#"{"a": true, "b": 1, "c": 0.5, "d": "x", "e": [1, 2]}"#)?;
+    assert_eq!(p.get("a"), Some(&ParameterValue::Bool(true)));
+    assert_eq!(p.get("b"), Some(&ParameterValue::Int(1)));
+    assert_eq!(p.get("c"), Some(&ParameterValue::Float(0.5)));
+    assert_eq!(p.get("d"), Some(&ParameterValue::String("x".into())));
+    assert_eq!(
+        p.get("e"),
+        Some(&ParameterValue::Array(vec![
+            ParameterValue::Int(1),
+            ParameterValue::Int(2)
+        ]))
+    );
+    assert!(Parameters::from_json("[1]").is_err());
+
+    let p = Parameters::from_json(
+        r#"{"learning_rate": 1.0, "lambda_l1": 0.0, "seed": 18446744073709551615}"#,
+    )?;
+    assert_eq!(p.get("learning_rate"), Some(&ParameterValue::Float(1.0)));
+    assert_eq!(p.get("lambda_l1"), Some(&ParameterValue::Float(0.0)));
+    assert_eq!(
+        p.get("seed"),
+        Some(&ParameterValue::USize(u64::MAX as usize))
+    );
+    Ok(())
+}
+
+#[test]
+fn leaf_value() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("num_leaves", 4);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    for _ in 0..3 {
+        b.update_one_iter()?;
+    }
+    let values: Vec<_> = b.leaf_values()?.collect();
+    assert!(!values.is_empty());
+    for &(tree_idx, leaf_idx, value) in &values {
+        assert!(tree_idx < 3);
+        assert_eq!(b.leaf_value(tree_idx, leaf_idx)?, value);
+    }
+
+    let (tree_idx, leaf_idx, _) = values[values.len() - 1];
+    b.set_leaf_value(tree_idx, leaf_idx, 1.5)?;
+    assert_eq!(b.leaf_value(tree_idx, leaf_idx)?, 1.5);
+
+    let num_leaves = values.iter().filter(|v| v.0 == tree_idx).count();
+    let e = b.leaf_value(tree_idx, num_leaves).unwrap_err();
+    assert_eq!(e.code(), None);
+    assert_eq!(
+        e.to_string(),
+        format!("leaf_idx must be less than {num_leaves}, but got {num_leaves}")
+    );
+    assert!(b.set_leaf_value(tree_idx, num_leaves, 0.0).is_err());
+    assert!(b.leaf_value(3, 0).is_err());
+    assert!(b.set_leaf_value(3, 0, 0.0).is_err());
+
+    b.update_one_iter()?;
+    assert!(b.leaf_value(3, 0).is_ok());
+    b.rollback_one_iter()?;
+    assert!(b.leaf_value(3, 0).is_err());
+    Ok(())
+}
+
+#[test]
+fn bound_value() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    for _ in 0..5 {
+        b.update_one_iter()?;
+    }
+    let upper = b.upper_bound_value()?;
+    let lower = b.lower_bound_value()?;
+    assert!(lower <= upper);
+
+    let features = make_features(100, 2);
+    let raw = b.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    assert!(
+        raw.values()
+            .iter()
+            .all(|&x| lower - 1e-9 <= x && x <= upper + 1e-9)
+    );
+
+    let pred = b.predict_for_mat(&features, PredictType::Normal, 0, None, &p)?;
+    let upper = Objective::Binary.convert_output(upper, &p)?.unwrap();
+    let lower = Objective::Binary.convert_output(lower, &p)?.unwrap();
+    assert!(
+        pred.values()
+            .iter()
+            .all(|&x| lower - 1e-9 <= x && x <= upper + 1e-9)
+    );
+    Ok(())
+}
+
+#[test]
+fn convert_output() -> Result<()> {
+    let p = Parameters::new();
+    assert_eq!(Objective::Binary.convert_output(0.0, &p)?, Some(0.5));
+    assert_eq!(Objective::Regression.convert_output(2.0, &p)?, Some(2.0));
+    assert_eq!(Objective::Poisson.convert_output(0.0, &p)?, Some(1.0));
+    assert_eq!(
+        Objective::RegressionL1.convert_output(-1.0, &p)?,
+        Some(-1.0)
+    );
+    assert_eq!(
+        Objective::CrossEntropyLambda.convert_output(0.0, &p)?,
+        Some(2f64.ln())
+    );
+    assert_eq!(Objective::Multiclass.convert_output(0.0, &p)?, None);
+
+    let mut p = Parameters::new();
+    p.push("sigmoid", 2.0);
+    p.push("reg_sqrt", true);
+    let expected = 1.0 / (1.0 + (-2.0f64).exp());
+    assert_eq!(Objective::Binary.convert_output(1.0, &p)?, Some(expected));
+    assert_eq!(
+        Objective::Multiclassova.convert_output(1.0, &p)?,
+        Some(expected)
+    );
+    assert_eq!(Objective::Regression.convert_output(-2.0, &p)?, Some(-4.0));
+
+    p.push("sigmoid", "x");
+    assert!(Objective::Binary.convert_output(1.0, &p).is_err());
+    Ok(())
+}
+
+#[test]
+fn merge() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+
+    let train = make_dataset(100, 2, None, &p)?;
+    let mut b0 = Booster::new(train.clone(), &p)?;
+    let mut b1 = Booster::new(train.clone(), &p)?;
+    for _ in 0..3 {
+        b0.update_one_iter()?;
+    }
+    for _ in 0..2 {
+        b1.update_one_iter()?;
+    }
+    let features = make_features(100, 2);
+    let raw1 = b1.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    b0.merge(&b1)?;
+    assert_eq!(b0.number_of_total_model()?, 5);
+    let raw01 = b0.predict_for_mat(&features, PredictType::RawScore, 0, Some(2), &p)?;
+    assert!(raw1.approx_eq(&raw01, 1e-6));
+
+    let raw = b0.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    b0.shuffle_models(0, None)?;
+    let shuffled = b0.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    assert!(raw.approx_eq(&shuffled, 1e-6));
+
+    let mut p3 = parameters();
+    p3.push("objective", Objective::Multiclass);
+    p3.push("num_class", 3);
+    let b3 = Booster::new(make_dataset(90, 3, None, &p3)?, &p3)?;
+    assert!(b0.merge(&b3).is_err());
+
+    let mut train = Dataset::from_mat(&features, None, &p)?;
+    train.set_field(Field::LABEL, &make_labels(100, 2))?;
+    train.set_feature_names(["a", "b"])?;
+    let b4 = Booster::new(Arc::new(train), &p)?;
+    assert!(b0.merge(&b4).is_err());
+    Ok(())
+}
+
+#[test]
+fn shap_values() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    p.push("num_leaves", 4);
+    p.push("min_data_in_leaf", 5);
+
+    let train = make_dataset(100, 3, None, &p)?;
+    let mut b = Booster::new(train, &p)?;
+    for _ in 0..5 {
+        b.update_one_iter()?;
+    }
+    let model = b.dump_model_typed(0, None, FeatureImportanceType::Split)?;
+    let features = make_features(20, 3);
+    for (start_iteration, num_iteration) in [(0, None), (1, Some(2))] {
+        let expected = b.predict_for_mat(
+            &features,
+            PredictType::Contrib,
+            start_iteration,
+            num_iteration,
+            &parameters(),
+        )?;
+        let actual = model.shap_values(features.as_slice(), start_iteration, num_iteration)?;
+        assert_eq!(actual.num_data(), 20);
+        assert_eq!(actual.num_class(), 3);
+        assert_eq!(actual.num_feature(), 2);
+        assert!(
+            actual.approx_eq(&expected, 1e-9),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    let num_column = model.max_feature_idx + 2;
+    let shap = model.shap_values(features.as_slice(), 0, None)?;
+    let interaction = model.shap_interaction_values(features.as_slice(), 0, None)?;
+    assert_eq!(interaction.num_data(), 20);
+    assert_eq!(interaction.num_class(), 3);
+    assert_eq!(interaction.values().len(), shap.values().len() * num_column);
+    for (row, &value) in interaction.values().chunks(num_column).zip(shap.values()) {
+        assert!((row.iter().sum::<f64>() - value).abs() < 1e-9);
+    }
+    Ok(())
+}
+
+#[test]
+fn shap_values_categorical() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("categorical_feature", [0]);
+    p.push("min_data_in_leaf", 5);
+    p.push("min_data_per_group", 5);
+
+    let mut train = Dataset::from_mat(make_features_categorycal(128, 3), None, &p)?;
+    train.set_field(Field::LABEL, &make_labels_categorycal(128, 3))?;
+    let mut b = Booster::new(Arc::new(train), &p)?;
+    for _ in 0..5 {
+        b.update_one_iter()?;
+    }
+    let model = b.dump_model_typed(0, None, FeatureImportanceType::Split)?;
+    let features = MatBuf::from_rows([[0.0, 1.0], [1.0, f64::NAN], [2.0, 5.0], [f64::NAN, 0.0]]);
+    let expected = b.predict_for_mat(&features, PredictType::Contrib, 0, None, &parameters())?;
+    let actual = model.shap_values(features.as_slice(), 0, None)?;
+    assert!(
+        actual.approx_eq(&expected, 1e-9),
+        "{actual:?} != {expected:?}"
+    );
+    Ok(())
+}
+
 fn make_features(num_row: usize, num_class: usize) -> MatBuf<f64, RowMajor> {
     MatBuf::from_rows((0..num_row).map(|x| [(x % num_class) as f64 + 1.0, x as f64]))
 }
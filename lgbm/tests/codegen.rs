@@ -0,0 +1,228 @@
+mod common;
+
+use anyhow::{Result, ensure};
+use common::{make_booster, make_features, parameters};
+use lgbm::{
+    Booster, FeatureImportanceType, PredictType,
+    codegen::{Language, generate_from_booster, generate_from_model_string},
+    parameters::Objective,
+};
+use std::{env, fmt::Write, fs, io::ErrorKind, path::Path, process::Command};
+
+#[test]
+fn codegen_binary() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    let b = make_booster(&p, |x| (x[0] > 0.3 || x[2] < -1.0) as i32 as f64)?;
+    check_codegen(&b, "binary", Language::Rust)
+}
+
+#[test]
+fn codegen_multiclass() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3) as f64)?;
+    check_codegen(&b, "multiclass", Language::Rust)
+}
+
+#[test]
+fn codegen_categorical() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("categorical_feature", [1]);
+    p.push("min_data_per_group", 5);
+    p.push("cat_smooth", 1.0);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3 == 1) as i32 as f64)?;
+    check_codegen(&b, "categorical", Language::Rust)
+}
+
+#[test]
+fn codegen_missing_zero() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    p.push("zero_as_missing", true);
+    let b = make_booster(&p, |x| x[1] + x[2])?;
+    check_codegen(&b, "missing_zero", Language::Rust)
+}
+
+#[test]
+fn codegen_linear_tree() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    p.push("linear_tree", true);
+    let b = make_booster(&p, |x| x[1] * 2.0 + x[2])?;
+    check_codegen(&b, "linear_tree", Language::Rust)
+}
+
+#[test]
+fn codegen_c() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    p.push("categorical_feature", [1]);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3) as f64)?;
+    check_codegen(&b, "c_multiclass", Language::C)?;
+
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("zero_as_missing", true);
+    let b = make_booster(&p, |x| (x[0] > 0.3 || x[2] < -1.0) as i32 as f64)?;
+    check_codegen(&b, "c_binary", Language::C)
+}
+
+#[test]
+fn codegen_model_string() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("categorical_feature", [1]);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3 == 1) as i32 as f64)?;
+    let expected = generate_from_booster(&b, Language::Rust)?;
+    let json = b.dump_model(0, None, FeatureImportanceType::Split)?;
+    let text = b.save_model_to_string(0, None, FeatureImportanceType::Split)?;
+    for model in [json, text] {
+        let actual = generate_from_model_string(model.to_str()?, Language::Rust)?;
+        assert_eq!(actual, expected);
+    }
+    Ok(())
+}
+
+/// Compile the generated code and compare its output with `predict_for_mat`.
+///
+/// C code is skipped if no C compiler is available.
+fn check_codegen(b: &Booster, name: &str, language: Language) -> Result<()> {
+    let dir = env::temp_dir().join(format!("lgbm-codegen-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let result = check_codegen_in(b, &dir, language);
+    fs::remove_dir_all(&dir)?;
+    result
+}
+fn check_codegen_in(b: &Booster, dir: &Path, language: Language) -> Result<()> {
+    let features = make_features(200, 1);
+    let mut input = String::new();
+    for row in features.as_slice().chunks(3) {
+        let row: Vec<String> = row.iter().map(|x| x.to_bits().to_string()).collect();
+        writeln!(input, "{}", row.join(" "))?;
+    }
+    fs::write(dir.join("input.txt"), input)?;
+
+    let code = generate_from_booster(b, language)?;
+    let exe = dir.join("main");
+    let status = match language {
+        Language::Rust => {
+            fs::write(dir.join("model.rs"), code)?;
+            fs::write(dir.join("main.rs"), MAIN_RS)?;
+            let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+            Command::new(rustc)
+                .arg("--edition=2021")
+                .arg("-O")
+                .arg("-Dwarnings")
+                .arg("-o")
+                .arg(&exe)
+                .arg(dir.join("main.rs"))
+                .status()?
+        }
+        Language::C => {
+            fs::write(dir.join("model.c"), code)?;
+            fs::write(dir.join("main.c"), MAIN_C)?;
+            let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+            let status = Command::new(cc)
+                .arg("-O2")
+                .arg("-ffp-contract=off")
+                .arg("-o")
+                .arg(&exe)
+                .arg(dir.join("main.c"))
+                .arg("-lm")
+                .status();
+            match status {
+                Ok(status) => status,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    eprintln!("skipped: C compiler not found");
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    };
+    ensure!(status.success(), "failed to compile generated code");
+    let output = Command::new(&exe).arg(dir.join("input.txt")).output()?;
+    ensure!(output.status.success(), "failed to run generated code");
+
+    let p = parameters();
+    let normal = b.predict_for_mat(&features, PredictType::Normal, 0, None, &p)?;
+    let raw = b.predict_for_mat(&features, PredictType::RawScore, 0, None, &p)?;
+    let num_class = normal.num_class();
+    let output = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), normal.num_data());
+    for (i, line) in lines.iter().enumerate() {
+        let actual: Vec<u64> = line
+            .split_whitespace()
+            .map(|x| x.parse())
+            .collect::<Result<_, _>>()?;
+        let expected: Vec<u64> = normal.values()[i * num_class..(i + 1) * num_class]
+            .iter()
+            .chain(&raw.values()[i * num_class..(i + 1) * num_class])
+            .map(|x| x.to_bits())
+            .collect();
+        assert_eq!(actual, expected, "row {i}");
+    }
+    Ok(())
+}
+
+const MAIN_RS: &str = r#"
+mod model;
+
+fn main() {
+    let input = std::fs::read_to_string(std::env::args().nth(1).unwrap()).unwrap();
+    for line in input.lines() {
+        let row: Vec<f64> = line
+            .split_whitespace()
+            .map(|x| f64::from_bits(x.parse().unwrap()))
+            .collect();
+        let values = model::predict(&row).into_iter().chain(model::predict_raw(&row));
+        let values: Vec<String> = values.map(|x| x.to_bits().to_string()).collect();
+        println!("{}", values.join(" "));
+    }
+}
+"#;
+
+const MAIN_C: &str = r#"
+#include <stdio.h>
+#include <string.h>
+#include "model.c"
+
+static void print_values(const double *values, int len) {
+    for (int i = 0; i < len; i++) {
+        uint64_t bits;
+        memcpy(&bits, &values[i], sizeof(bits));
+        printf(i == 0 ? "%llu" : " %llu", (unsigned long long)bits);
+    }
+}
+
+int main(int argc, char **argv) {
+    FILE *input = fopen(argv[1], "r");
+    if (!input) {
+        return 1;
+    }
+    double row[LGBM_NUM_FEATURE];
+    unsigned long long bits;
+    int n = 0;
+    while (fscanf(input, "%llu", &bits) == 1) {
+        memcpy(&row[n], &bits, sizeof(double));
+        if (++n < LGBM_NUM_FEATURE) {
+            continue;
+        }
+        n = 0;
+        double out[LGBM_NUM_CLASS];
+        lgbm_predict(row, out);
+        print_values(out, LGBM_NUM_CLASS);
+        printf(" ");
+        lgbm_predict_raw(row, out);
+        print_values(out, LGBM_NUM_CLASS);
+        printf("\n");
+    }
+    fclose(input);
+    return 0;
+}
+"#;
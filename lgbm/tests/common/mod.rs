@@ -0,0 +1,67 @@
+//! Fixtures shared by the tests that compare other predictors with [`Booster`].
+#![allow(dead_code)]
+
+use anyhow::Result;
+use lgbm::{
+    Booster, Dataset, Field, MatBuf, Parameters, mat::RowMajor, parameters::Verbosity, train::train,
+};
+use std::sync::Arc;
+
+/// Train a booster on 500 rows of [`make_features`].
+pub fn make_booster(p: &Parameters, label: impl Fn(&[f64]) -> f64) -> Result<Booster> {
+    train_booster(p, &make_features(500, 0), label)
+}
+
+/// Train a booster for 10 iterations on `features` labeled by `label`.
+pub fn train_booster(
+    p: &Parameters,
+    features: &MatBuf<f64, RowMajor>,
+    label: impl Fn(&[f64]) -> f64,
+) -> Result<Booster> {
+    let labels: Vec<f32> = features
+        .as_slice()
+        .chunks(3)
+        .map(|x| label(x) as f32)
+        .collect();
+    let mut d = Dataset::from_mat(features, None, p)?;
+    d.set_field(Field::LABEL, &labels)?;
+    Ok(train(p, Arc::new(d), &[], 10, &mut [])?.booster)
+}
+
+/// Rows of 3 features generated from `seed`.
+///
+/// The first feature contains NaN and zero, the second one has integer values in `0..6`,
+/// and the third one contains zero and values smaller than the zero threshold of LightGBM.
+pub fn make_features(num_row: usize, seed: u64) -> MatBuf<f64, RowMajor> {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    MatBuf::from_rows((0..num_row).map(|_| {
+        let x0 = match next() {
+            r if r < 0.1 => f64::NAN,
+            r if r < 0.2 => 0.0,
+            _ => next() * 2.0 - 1.0,
+        };
+        let x1 = (next() * 6.0).floor();
+        let x2 = match next() {
+            r if r < 0.2 => 0.0,
+            r if r < 0.25 => 1e-40,
+            _ => next() * 4.0 - 2.0,
+        };
+        [x0, x1, x2]
+    }))
+}
+
+pub fn parameters() -> Parameters {
+    let mut p = Parameters::new();
+    p.push("verbosity", Verbosity::Fatal);
+    p.push("num_leaves", 7);
+    p.push("min_data_in_leaf", 5);
+    p
+}
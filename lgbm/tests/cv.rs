@@ -0,0 +1,127 @@
+use anyhow::Result;
+use lgbm::{
+    Dataset, Field, MatBuf, Parameters,
+    cv::{CvOptions, Fold, Folds, cv, make_folds},
+    mat::RowMajor,
+    parameters::{Metric, Objective, Verbosity},
+};
+
+#[test]
+fn cv_binary() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", [Metric::BinaryLogloss, Metric::Auc]);
+
+    let d = make_dataset(120, 2, &p)?;
+    let options = CvOptions {
+        num_boost_round: 10,
+        folds: Folds::NumFold(3),
+        ..Default::default()
+    };
+    let r = cv(&d, &p, &options)?;
+    assert_eq!(r.boosters.len(), 3);
+    assert_eq!(r.metrics.len(), 2);
+    for m in &r.metrics {
+        assert_eq!(m.mean.len(), 10);
+        assert_eq!(m.stdv.len(), 10);
+        assert!(m.stdv.iter().all(|&s| s >= 0.0));
+    }
+    Ok(())
+}
+
+#[test]
+fn make_folds_stratified() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+
+    let d = make_dataset(90, 3, &p)?;
+    let options = CvOptions {
+        folds: Folds::NumFold(3),
+        ..Default::default()
+    };
+    let folds = make_folds(&d, &p, &options)?;
+    assert_eq!(folds.len(), 3);
+    let label = d.get_field(Field::LABEL)?;
+    for fold in &folds {
+        assert_eq!(fold.train.len() + fold.valid.len(), 90);
+        for class in 0..3 {
+            let n = fold
+                .valid
+                .iter()
+                .filter(|&&i| label[i as usize] == class as f32)
+                .count();
+            assert_eq!(n, 10);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn make_folds_group() -> Result<()> {
+    let p = parameters();
+    let mut d = Dataset::from_mat(make_features(12, 2), None, &p)?;
+    d.set_field(Field::LABEL, &make_labels(12, 2))?;
+    d.set_field(Field::GROUP, &[3, 3, 3, 3])?;
+
+    let options = CvOptions {
+        folds: Folds::NumFold(2),
+        ..Default::default()
+    };
+    let folds = make_folds(&d, &p, &options)?;
+    for fold in &folds {
+        assert_eq!(fold.valid.len(), 6);
+        for query in fold.valid.chunks(3) {
+            assert_eq!(query[0] % 3, 0);
+            assert_eq!(query[1], query[0] + 1);
+            assert_eq!(query[2], query[0] + 2);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn make_folds_explicit() -> Result<()> {
+    let p = parameters();
+    let d = make_dataset(4, 2, &p)?;
+    let fold = Fold {
+        train: vec![3, 1],
+        valid: vec![2, 0],
+    };
+    let options = CvOptions {
+        folds: Folds::Explicit(vec![fold]),
+        ..Default::default()
+    };
+    let folds = make_folds(&d, &p, &options)?;
+    assert_eq!(folds[0].train, vec![1, 3]);
+    assert_eq!(folds[0].valid, vec![0, 2]);
+
+    let fold = Fold {
+        train: vec![0],
+        valid: vec![4],
+    };
+    let options = CvOptions {
+        folds: Folds::Explicit(vec![fold]),
+        ..Default::default()
+    };
+    assert!(make_folds(&d, &p, &options).is_err());
+    Ok(())
+}
+
+fn make_features(num_row: usize, num_class: usize) -> MatBuf<f64, RowMajor> {
+    MatBuf::from_rows((0..num_row).map(|x| [(x % num_class) as f64 + 1.0, x as f64]))
+}
+fn make_labels(num_row: usize, num_class: usize) -> Vec<f32> {
+    (0..num_row).map(|x| (x % num_class) as f32).collect()
+}
+fn make_dataset(num_row: usize, num_class: usize, p: &Parameters) -> Result<Dataset> {
+    let mut d = Dataset::from_mat(make_features(num_row, num_class), None, p)?;
+    d.set_field(Field::LABEL, &make_labels(num_row, num_class))?;
+    Ok(d)
+}
+
+fn parameters() -> Parameters {
+    let mut p = Parameters::new();
+    p.push("verbosity", Verbosity::Fatal);
+    p
+}
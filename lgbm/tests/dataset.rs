@@ -1,5 +1,10 @@
 use anyhow::Result;
-use lgbm::{Dataset, Field, MatBuf, Parameters, parameters::Verbosity};
+use lgbm::{
+    Dataset, DatasetBuilder, Field, MatBuf, Parameters, RowsMetadata, SparseMatBuf,
+    StreamingOptions,
+    mat::{Csc, Csr},
+    parameters::Verbosity,
+};
 use std::env;
 
 #[test]
@@ -54,6 +59,62 @@ fn from_mats() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn from_csr() -> Result<()> {
+    let csr = SparseMatBuf::<f64, i32, Csr>::from_vecs(
+        vec![0, 2, 3],
+        vec![0, 2, 1],
+        vec![1.0, 3.0, 5.0],
+        2,
+        3,
+        Csr,
+    )?;
+    let d = Dataset::from_csr(&csr, None, &parameters())?;
+    assert_eq!(d.get_num_feature()?, 3);
+    assert_eq!(d.get_num_data()?, 2);
+    Ok(())
+}
+
+#[test]
+fn from_csr_i64() -> Result<()> {
+    let csr = SparseMatBuf::<f32, i64, Csr>::from_dense(MatBuf::from_rows([
+        [1.0f32, 0.0, 3.0],
+        [0.0, 5.0, 0.0],
+    ]))?;
+    assert_eq!(csr.indptr(), &[0, 2, 3]);
+    let d = Dataset::from_csr(&csr, None, &parameters())?;
+    assert_eq!(d.get_num_feature()?, 3);
+    assert_eq!(d.get_num_data()?, 2);
+    Ok(())
+}
+
+#[test]
+fn from_csc() -> Result<()> {
+    let csc = SparseMatBuf::<f64, i32, Csc>::from_dense(MatBuf::from_rows([
+        [1.0f64, 0.0, 3.0],
+        [0.0, 5.0, 0.0],
+    ]))?;
+    assert_eq!(csc.indptr(), &[0, 1, 2, 3]);
+    let d = Dataset::from_csc(&csc, None, &parameters())?;
+    assert_eq!(d.get_num_feature()?, 3);
+    assert_eq!(d.get_num_data()?, 2);
+    Ok(())
+}
+
+#[test]
+fn sparse_mat_invalid() {
+    let new = |indptr: Vec<i32>, indices: Vec<i32>, values: Vec<f64>| {
+        SparseMatBuf::from_vecs(indptr, indices, values, 2, 3, Csr)
+    };
+    assert!(new(vec![0, 1], vec![0], vec![1.0]).is_err());
+    assert!(new(vec![1, 1, 1], vec![0], vec![1.0]).is_err());
+    assert!(new(vec![0, 2, 1], vec![0, 1], vec![1.0, 2.0]).is_err());
+    assert!(new(vec![0, 1, 2], vec![0, 1], vec![1.0]).is_err());
+    assert!(new(vec![0, 1, 2], vec![0, 3], vec![1.0, 2.0]).is_err());
+    assert!(new(vec![0, 1, 2], vec![0, -1], vec![1.0, 2.0]).is_err());
+    assert!(new(vec![0, 1, 2], vec![0, 2], vec![1.0, 2.0]).is_ok());
+}
+
 #[test]
 fn set_label() -> Result<()> {
     let mut d = Dataset::from_mat(
@@ -96,6 +157,146 @@ fn set_feature_names_string() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn subset() -> Result<()> {
+    let mut d = Dataset::from_mat(
+        MatBuf::from_rows([[1.0f64, 2.0], [3.0, 4.0], [5.0, 6.0], [7.0, 8.0]]),
+        None,
+        &parameters(),
+    )?;
+    d.set_field(Field::LABEL, &[0.0, 1.0, 2.0, 3.0])?;
+    d.set_field(Field::WEIGHT, &[1.0, 2.0, 3.0, 4.0])?;
+
+    let s = d.subset(&[1, 3], &parameters())?;
+    assert_eq!(s.get_num_feature()?, 2);
+    assert_eq!(s.get_num_data()?, 2);
+    assert_eq!(s.get_field(Field::LABEL)?, &[1.0, 3.0]);
+    assert_eq!(s.get_field(Field::WEIGHT)?, &[2.0, 4.0]);
+
+    assert!(d.subset(&[], &parameters()).is_err());
+    assert!(d.subset(&[0, 4], &parameters()).is_err());
+    assert!(d.subset(&[-1], &parameters()).is_err());
+    assert!(d.subset(&[2, 1], &parameters()).is_err());
+    Ok(())
+}
+
+#[test]
+fn dataset_builder() -> Result<()> {
+    let reference = Dataset::from_mat(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]),
+        None,
+        &parameters(),
+    )?;
+    let options = StreamingOptions {
+        has_weights: true,
+        ..Default::default()
+    };
+    let mut b = DatasetBuilder::new(&reference, 5, options)?;
+    b.push_rows_with_metadata(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]),
+        RowsMetadata {
+            label: &[0.0, 1.0],
+            weight: Some(&[1.0, 2.0]),
+            ..Default::default()
+        },
+    )?;
+    let csr = SparseMatBuf::<f64, i32, Csr>::from_dense(MatBuf::from_rows([
+        [1.0f64, 0.0, 3.0],
+        [0.0, 5.0, 0.0],
+        [7.0, 8.0, 0.0],
+    ]))?;
+    b.push_rows_by_csr_with_metadata(
+        &csr,
+        RowsMetadata {
+            label: &[1.0, 0.0, 1.0],
+            weight: Some(&[3.0, 4.0, 5.0]),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(b.num_pushed_row(), 5);
+    let d = b.finish()?;
+    assert_eq!(d.get_num_feature()?, 3);
+    assert_eq!(d.get_num_data()?, 5);
+    assert_eq!(d.get_field(Field::LABEL)?, &[0.0, 1.0, 1.0, 0.0, 1.0]);
+    assert_eq!(d.get_field(Field::WEIGHT)?, &[1.0, 2.0, 3.0, 4.0, 5.0]);
+    Ok(())
+}
+
+#[test]
+fn save_binary() -> Result<()> {
+    let mut d = Dataset::from_mat(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]),
+        None,
+        &parameters(),
+    )?;
+    d.set_field(Field::LABEL, &[0.0, 1.0])?;
+
+    let path = env::temp_dir().join(format!("lgbm-save-binary-{}.bin", std::process::id()));
+    d.save_binary(&path)?;
+    let d1 = Dataset::from_binary(&path, &parameters());
+    std::fs::remove_file(&path)?;
+    let d1 = d1?;
+    assert_eq!(d1.get_num_feature()?, 3);
+    assert_eq!(d1.get_num_data()?, 2);
+    assert_eq!(d1.get_field(Field::LABEL)?, &[0.0, 1.0]);
+    Ok(())
+}
+
+#[test]
+fn serialize_reference() -> Result<()> {
+    let reference = Dataset::from_mat(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]),
+        None,
+        &parameters(),
+    )?;
+    let buffer = reference.serialize_reference()?;
+    assert!(!buffer.is_empty());
+    let bytes = buffer.to_vec()?;
+    assert_eq!(bytes.len(), buffer.len());
+    drop(reference);
+
+    let mut b =
+        DatasetBuilder::from_serialized_reference(&bytes, 3, Default::default(), &parameters())?;
+    b.push_rows_with_metadata(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]),
+        RowsMetadata {
+            label: &[0.0, 1.0, 1.0],
+            ..Default::default()
+        },
+    )?;
+    let d = b.finish()?;
+    assert_eq!(d.get_num_feature()?, 3);
+    assert_eq!(d.get_num_data()?, 3);
+    Ok(())
+}
+
+#[test]
+fn dataset_builder_invalid() -> Result<()> {
+    let reference = Dataset::from_mat(
+        MatBuf::from_rows([[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]),
+        None,
+        &parameters(),
+    )?;
+    let mut b = DatasetBuilder::new(&reference, 2, StreamingOptions::default())?;
+    assert!(b.push_rows(MatBuf::from_rows([[1.0f64, 2.0]])).is_err());
+    assert!(
+        b.push_rows(MatBuf::from_rows([[1.0f64, 2.0, 3.0]; 3]))
+            .is_err()
+    );
+    let metadata = RowsMetadata {
+        label: &[0.0],
+        weight: Some(&[1.0]),
+        ..Default::default()
+    };
+    assert!(
+        b.push_rows_with_metadata(MatBuf::from_rows([[1.0f64, 2.0, 3.0]]), metadata)
+            .is_err()
+    );
+    b.push_rows(MatBuf::from_rows([[1.0f64, 2.0, 3.0]]))?;
+    assert!(b.finish().is_err());
+    Ok(())
+}
+
 fn parameters() -> Parameters {
     let mut p = Parameters::new();
     p.push("verbosity", Verbosity::Fatal);
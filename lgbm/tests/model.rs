@@ -0,0 +1,238 @@
+use anyhow::Result;
+use lgbm::{
+    Booster, Dataset, FeatureImportanceType, Field, MatBuf, Parameters,
+    model::{DecisionType, MissingType, ModelDump, Node, Threshold},
+    parameters::{Objective, Verbosity},
+};
+use std::sync::Arc;
+
+#[test]
+fn parse_model_dump() -> Result<()> {
+    let json = r#"{
+        "name": "tree",
+        "version": "v4",
+        "num_class": 1,
+        "num_tree_per_iteration": 1,
+        "label_index": 0,
+        "max_feature_idx": 1,
+        "objective": "binary sigmoid:1",
+        "average_output": false,
+        "feature_names": ["a", "b"],
+        "monotone_constraints": [],
+        "feature_infos": {
+            "a": {"min_value": 0, "max_value": 3, "values": [0, 1, 2, 3]},
+            "b": {"min_value": -1.5, "max_value": 2.5, "values": []}
+        },
+        "tree_info": [
+            {
+                "tree_index": 0,
+                "num_leaves": 3,
+                "num_cat": 1,
+                "shrinkage": 1,
+                "tree_structure": {
+                    "split_index": 0,
+                    "split_feature": 0,
+                    "split_gain": 10.5,
+                    "threshold": "0||2",
+                    "decision_type": "==",
+                    "default_left": false,
+                    "missing_type": "None",
+                    "internal_value": 0,
+                    "internal_weight": 10,
+                    "internal_count": 40,
+                    "left_child": {
+                        "leaf_index": 0,
+                        "leaf_value": 0.5,
+                        "leaf_weight": 5,
+                        "leaf_count": 20
+                    },
+                    "right_child": {
+                        "split_index": 1,
+                        "split_feature": 1,
+                        "split_gain": 1.25,
+                        "threshold": 0.5,
+                        "decision_type": "<=",
+                        "default_left": true,
+                        "missing_type": "NaN",
+                        "internal_value": -0.5,
+                        "internal_weight": 5,
+                        "internal_count": 20,
+                        "left_child": {
+                            "leaf_index": 1,
+                            "leaf_value": -0.25,
+                            "leaf_weight": 2.5,
+                            "leaf_count": 10,
+                            "leaf_const": 0.1,
+                            "leaf_features": [1],
+                            "leaf_coeff": [0.2]
+                        },
+                        "right_child": {
+                            "leaf_index": 2,
+                            "leaf_value": -0.75,
+                            "leaf_weight": 2.5,
+                            "leaf_count": 10
+                        }
+                    }
+                }
+            },
+            {
+                "tree_index": 1,
+                "num_leaves": 1,
+                "num_cat": 0,
+                "shrinkage": 1,
+                "tree_structure": {"leaf_value": 0}
+            }
+        ],
+        "feature_importances": {"a": 1, "b": 1},
+        "pandas_categorical": null
+    }"#;
+    let model: ModelDump = serde_json::from_str(json)?;
+    assert_eq!(model.feature_names, ["a", "b"]);
+    assert_eq!(model.feature_infos["a"].values, [0, 1, 2, 3]);
+    assert_eq!(model.tree_info.len(), 2);
+
+    let tree = &model.tree_info[0];
+    assert_eq!(tree.depth(), 2);
+    let Node::Split(root) = &tree.tree_structure else {
+        panic!("root is not a split");
+    };
+    assert_eq!(root.threshold, Threshold::Categorical(vec![0, 2]));
+    assert_eq!(root.decision_type, DecisionType::Equal);
+    let Node::Split(right) = &root.right_child else {
+        panic!("right child is not a split");
+    };
+    assert_eq!(right.threshold, Threshold::Numerical(0.5));
+    assert_eq!(right.decision_type, DecisionType::LessOrEqual);
+    assert_eq!(right.missing_type, MissingType::NaN);
+
+    let leaves = tree.leaves();
+    assert_eq!(leaves.len(), 3);
+    assert_eq!(leaves[1].leaf_const, Some(0.1));
+    assert_eq!(leaves[1].leaf_coeff, [0.2]);
+    assert_eq!(model.tree_info[1].depth(), 0);
+    assert_eq!(model.tree_info[1].leaves()[0].leaf_value, 0.0);
+
+    let model1: ModelDump = serde_json::from_str(&serde_json::to_string(&model)?)?;
+    assert_eq!(model, model1);
+    Ok(())
+}
+
+#[test]
+fn dump_model_typed() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("num_leaves", 4);
+
+    let features = MatBuf::from_rows((0..100).map(|x| [(x % 4) as f64, x as f64]));
+    let mut d = Dataset::from_mat(&features, None, &p)?;
+    d.set_field(Field::LABEL, &make_labels(100))?;
+    let mut b = Booster::new(Arc::new(d), &p)?;
+    for _ in 0..3 {
+        b.update_one_iter()?;
+    }
+    let model = b.dump_model_typed(0, None, FeatureImportanceType::Split)?;
+    assert_eq!(model.num_class, 1);
+    assert_eq!(model.feature_names.len(), 2);
+    assert_eq!(model.tree_info.len(), 3);
+    for tree in &model.tree_info {
+        let leaves = tree.leaves();
+        assert_eq!(leaves.len(), tree.num_leaves);
+        for leaf in leaves {
+            assert_eq!(
+                b.leaf_value(tree.tree_index, leaf.leaf_index)?,
+                leaf.leaf_value
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn shap_values() -> Result<()> {
+    let json = r#"{
+        "name": "tree",
+        "version": "v4",
+        "num_class": 1,
+        "num_tree_per_iteration": 1,
+        "label_index": 0,
+        "max_feature_idx": 1,
+        "feature_names": ["a", "b"],
+        "tree_info": [
+            {
+                "tree_index": 0,
+                "num_leaves": 3,
+                "num_cat": 0,
+                "shrinkage": 1,
+                "tree_structure": {
+                    "split_index": 0,
+                    "split_feature": 0,
+                    "split_gain": 1,
+                    "threshold": 0.5,
+                    "decision_type": "<=",
+                    "default_left": true,
+                    "missing_type": "None",
+                    "internal_value": 0,
+                    "internal_weight": 40,
+                    "internal_count": 40,
+                    "left_child": {"leaf_index": 0, "leaf_value": 1, "leaf_count": 10},
+                    "right_child": {
+                        "split_index": 1,
+                        "split_feature": 1,
+                        "split_gain": 1,
+                        "threshold": 0.5,
+                        "decision_type": "<=",
+                        "default_left": true,
+                        "missing_type": "None",
+                        "internal_value": 0,
+                        "internal_weight": 30,
+                        "internal_count": 30,
+                        "left_child": {"leaf_index": 1, "leaf_value": 2, "leaf_count": 10},
+                        "right_child": {"leaf_index": 2, "leaf_value": 3, "leaf_count": 20}
+                    }
+                }
+            }
+        ]
+    }"#;
+    let model: ModelDump = serde_json::from_str(json)?;
+    let shap = model.shap_values(&[1.0, 1.0], 0, None)?;
+    assert_approx_eq(
+        shap.values(),
+        &[0.5 * (5.0 / 12.0 + 0.5), 0.5 * (0.25 + 1.0 / 3.0), 2.25],
+    );
+
+    let interaction = model.shap_interaction_values(&[1.0, 1.0], 0, None)?;
+    let phi01 = 0.5 * (3.0 - 8.0 / 3.0 - 2.5 + 2.25);
+    assert_approx_eq(
+        interaction.values(),
+        &[
+            shap.values()[0] - phi01,
+            phi01,
+            0.0,
+            phi01,
+            shap.values()[1] - phi01,
+            0.0,
+            0.0,
+            0.0,
+            2.25,
+        ],
+    );
+    assert!(model.shap_values(&[1.0, 1.0, 1.0], 0, None).is_err());
+    Ok(())
+}
+
+fn assert_approx_eq(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-12, "{actual:?} != {expected:?}");
+    }
+}
+
+fn make_labels(num_row: usize) -> Vec<f32> {
+    (0..num_row).map(|x| (x % 2) as f32).collect()
+}
+
+fn parameters() -> Parameters {
+    let mut p = Parameters::new();
+    p.push("verbosity", Verbosity::Fatal);
+    p
+}
@@ -0,0 +1,545 @@
+#[cfg(feature = "lightgbm")]
+mod common;
+
+use anyhow::{Result, bail};
+#[cfg(feature = "lightgbm")]
+use common::{parameters, train_booster};
+#[cfg(feature = "lightgbm")]
+use lgbm::{
+    Booster, MatBuf, Parameters, PredictType, mat::RowMajor, onnx::export_from_booster,
+    parameters::Objective,
+};
+use lgbm::{
+    model::{ModelDump, Node},
+    onnx::export,
+};
+use std::collections::HashMap;
+
+#[test]
+fn export_binary() -> Result<()> {
+    let model: ModelDump = serde_json::from_str(MODEL)?;
+    let bytes = export(&model)?;
+    let m = decode(&bytes)?;
+    assert_eq!(m.varint(1), Some(8));
+    let opsets: Vec<String> = m
+        .messages(8)?
+        .iter()
+        .map(|o| o.string(1).unwrap_or_default())
+        .collect();
+    assert_eq!(opsets, ["", "ai.onnx.ml"]);
+
+    let graph = &m.messages(7)?[0];
+    let nodes = graph.messages(1)?;
+    assert_eq!(nodes.len(), 1);
+    let node = &nodes[0];
+    assert_eq!(node.string(4).as_deref(), Some("TreeEnsembleClassifier"));
+    assert_eq!(node.string(7).as_deref(), Some("ai.onnx.ml"));
+    let attrs = Attributes::new(node)?;
+    assert_eq!(attrs.string("post_transform"), "LOGISTIC");
+    assert_eq!(attrs.ints("classlabels_int64s"), [0, 1]);
+
+    for row in ROWS {
+        let row64 = row.map(|x| x as f64);
+        let expected: f64 = model
+            .tree_info
+            .iter()
+            .map(|tree| leaf_value(&tree.tree_structure, &row64))
+            .sum();
+        let actual = attrs.eval(row, "class");
+        assert_eq!(actual.len(), 1);
+        assert!(
+            (actual[0] as f64 - expected * 2.0).abs() < 1e-6,
+            "{row:?}: {actual:?} != {expected} * 2"
+        );
+        let p = 1.0 / (1.0 + (-expected * 2.0).exp());
+        let actual = attrs.probabilities(row);
+        assert!(
+            (actual[0] as f64 - (1.0 - p)).abs() < 1e-6 && (actual[1] as f64 - p).abs() < 1e-6,
+            "{row:?}: {actual:?} != [1 - {p}, {p}]"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn export_regression() -> Result<()> {
+    let mut model: ModelDump = serde_json::from_str(MODEL)?;
+    model.objective = Some("poisson".to_string());
+    let m = decode(&export(&model)?)?;
+    let graph = &m.messages(7)?[0];
+    let ops: Vec<String> = graph
+        .messages(1)?
+        .iter()
+        .map(|n| n.string(4).unwrap_or_default())
+        .collect();
+    assert_eq!(ops, ["TreeEnsembleRegressor", "Exp"]);
+    let attrs = Attributes::new(&graph.messages(1)?[0])?;
+    assert_eq!(attrs.string("post_transform"), "NONE");
+    assert_eq!(attrs.ints("n_targets"), [1]);
+
+    model.objective = Some("lambdarank".to_string());
+    model.tree_info[1].tree_structure = Node::Leaf(serde_json::from_str(
+        r#"{"leaf_value": 1, "leaf_const": 1, "leaf_features": [0], "leaf_coeff": [1]}"#,
+    )?);
+    assert!(export(&model).is_err());
+    Ok(())
+}
+
+#[test]
+fn export_multiclass() -> Result<()> {
+    let mut model: ModelDump = serde_json::from_str(MODEL)?;
+    model.objective = Some("multiclass num_class:2".to_string());
+    model.num_class = 2;
+    model.num_tree_per_iteration = 2;
+    let m = decode(&export(&model)?)?;
+    let graph = &m.messages(7)?[0];
+    let nodes = graph.messages(1)?;
+    assert_eq!(nodes.len(), 1);
+    let attrs = Attributes::new(&nodes[0])?;
+    assert_eq!(attrs.string("post_transform"), "SOFTMAX");
+    assert_eq!(attrs.ints("classlabels_int64s"), [0, 1]);
+
+    for row in ROWS {
+        let row64 = row.map(|x| x as f64);
+        let expected: Vec<f64> = (0..2)
+            .map(|k| leaf_value(&model.tree_info[k].tree_structure, &row64))
+            .collect();
+        let actual = attrs.eval(row, "class");
+        assert_eq!(actual.len(), 2);
+        for k in 0..2 {
+            assert!(
+                (actual[k] as f64 - expected[k]).abs() < 1e-6,
+                "{row:?}: {actual:?} != {expected:?}"
+            );
+        }
+        let sum = expected[0].exp() + expected[1].exp();
+        let actual = attrs.probabilities(row);
+        for k in 0..2 {
+            let p = expected[k].exp() / sum;
+            assert!(
+                (actual[k] as f64 - p).abs() < 1e-6,
+                "{row:?}: {actual:?}[{k}] != {p}"
+            );
+        }
+    }
+
+    model.objective = Some("multiclassova num_class:2 sigmoid:1.5".to_string());
+    let m = decode(&export(&model)?)?;
+    let attrs = Attributes::new(&m.messages(7)?[0].messages(1)?[0])?;
+    assert_eq!(attrs.string("post_transform"), "LOGISTIC");
+    for row in ROWS {
+        let row64 = row.map(|x| x as f64);
+        let actual = attrs.probabilities(row);
+        for k in 0..2 {
+            let raw = leaf_value(&model.tree_info[k].tree_structure, &row64);
+            let p = 1.0 / (1.0 + (-1.5 * raw).exp());
+            assert!(
+                (actual[k] as f64 - p).abs() < 1e-6,
+                "{row:?}: {actual:?}[{k}] != {p}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "lightgbm")]
+#[test]
+fn export_from_booster_binary() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("categorical_feature", [1]);
+    let b = make_booster(&p, |x| (x[0] > 0.3 || x[1] == 2.0) as i32 as f64)?;
+    check_booster(&b)
+}
+
+#[cfg(feature = "lightgbm")]
+#[test]
+fn export_from_booster_multiclass() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    p.push("zero_as_missing", true);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3) as f64)?;
+    check_booster(&b)
+}
+
+#[cfg(feature = "lightgbm")]
+#[test]
+fn export_from_booster_sigmoid() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("sigmoid", 0.5);
+    let b = make_booster(&p, |x| (x[0] > 0.3 || x[2] < -1.0) as i32 as f64)?;
+    check_booster(&b)?;
+
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclassova);
+    p.push("num_class", 3);
+    p.push("sigmoid", 1.5);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3) as f64)?;
+    check_booster(&b)
+}
+
+/// Compare the probabilities of the exported model with `predict_for_mat`.
+#[cfg(feature = "lightgbm")]
+fn check_booster(b: &Booster) -> Result<()> {
+    let m = decode(&export_from_booster(b)?)?;
+    let attrs = Attributes::new(&m.messages(7)?[0].messages(1)?[0])?;
+    let features = make_features(200, 1);
+    let expected = b.predict_for_mat(&features, PredictType::Normal, 0, None, &parameters())?;
+    let num_class = expected.num_class();
+    for (i, row) in features.as_slice().chunks(3).enumerate() {
+        let row: Vec<f32> = row.iter().map(|&x| x as f32).collect();
+        let actual = attrs.probabilities(&row);
+        let expected = &expected.values()[i * num_class..(i + 1) * num_class];
+        let expected: Vec<f64> = if num_class == 1 {
+            vec![1.0 - expected[0], expected[0]]
+        } else {
+            expected.to_vec()
+        };
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!(
+                (*a as f64 - e).abs() < 1e-5,
+                "row {i}: {actual:?} != {expected:?}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "lightgbm")]
+fn make_booster(p: &Parameters, label: impl Fn(&[f64]) -> f64) -> Result<Booster> {
+    train_booster(p, &make_features(500, 0), label)
+}
+
+/// [`common::make_features`] rounded to `f32`, the input type of the exported model.
+///
+/// Values that LightGBM treats as zero are replaced by zero, since the exported model compares them as is.
+#[cfg(feature = "lightgbm")]
+fn make_features(num_row: usize, seed: u64) -> MatBuf<f64, RowMajor> {
+    let features = common::make_features(num_row, seed);
+    MatBuf::from_vec(
+        features
+            .as_slice()
+            .iter()
+            .map(|&x| {
+                if x.abs() <= 1e-35 {
+                    0.0
+                } else {
+                    x as f32 as f64
+                }
+            })
+            .collect(),
+        num_row,
+        3,
+        RowMajor,
+    )
+}
+
+fn leaf_value(node: &Node, row: &[f64]) -> f64 {
+    match node {
+        Node::Split(split) => {
+            if split.goes_left(row[split.split_feature]) {
+                leaf_value(&split.left_child, row)
+            } else {
+                leaf_value(&split.right_child, row)
+            }
+        }
+        Node::Leaf(leaf) => leaf.leaf_value,
+    }
+}
+
+const ROWS: &[[f32; 2]] = &[
+    [0.0, 0.0],
+    [1.0, 0.0],
+    [3.0, f32::NAN],
+    [2.0, f32::NAN],
+    [f32::NAN, -1.0],
+    [-0.5, -0.4],
+    [2.0, 0.2],
+    [0.0, 0.3],
+    [0.0, -0.5],
+];
+
+const MODEL: &str = r#"{
+    "name": "tree",
+    "version": "v4",
+    "num_class": 1,
+    "num_tree_per_iteration": 1,
+    "label_index": 0,
+    "max_feature_idx": 1,
+    "objective": "binary sigmoid:2",
+    "feature_names": ["a", "b"],
+    "tree_info": [
+        {
+            "tree_index": 0,
+            "num_leaves": 3,
+            "num_cat": 1,
+            "shrinkage": 1,
+            "tree_structure": {
+                "split_index": 0,
+                "split_feature": 0,
+                "split_gain": 1,
+                "threshold": "1||3",
+                "decision_type": "==",
+                "default_left": false,
+                "missing_type": "NaN",
+                "internal_value": 0,
+                "internal_weight": 0,
+                "internal_count": 0,
+                "left_child": {"leaf_index": 0, "leaf_value": 0.5},
+                "right_child": {
+                    "split_index": 1,
+                    "split_feature": 1,
+                    "split_gain": 1,
+                    "threshold": -0.5,
+                    "decision_type": "<=",
+                    "default_left": true,
+                    "missing_type": "Zero",
+                    "internal_value": 0,
+                    "internal_weight": 0,
+                    "internal_count": 0,
+                    "left_child": {"leaf_index": 1, "leaf_value": -0.25},
+                    "right_child": {"leaf_index": 2, "leaf_value": 0.75}
+                }
+            }
+        },
+        {
+            "tree_index": 1,
+            "num_leaves": 3,
+            "num_cat": 0,
+            "shrinkage": 1,
+            "tree_structure": {
+                "split_index": 0,
+                "split_feature": 1,
+                "split_gain": 1,
+                "threshold": 0.25,
+                "decision_type": "<=",
+                "default_left": false,
+                "missing_type": "NaN",
+                "internal_value": 0,
+                "internal_weight": 0,
+                "internal_count": 0,
+                "left_child": {
+                    "split_index": 1,
+                    "split_feature": 1,
+                    "split_gain": 1,
+                    "threshold": -1,
+                    "decision_type": "<=",
+                    "default_left": true,
+                    "missing_type": "None",
+                    "internal_value": 0,
+                    "internal_weight": 0,
+                    "internal_count": 0,
+                    "left_child": {"leaf_index": 0, "leaf_value": 0.125},
+                    "right_child": {"leaf_index": 1, "leaf_value": -0.125}
+                },
+                "right_child": {"leaf_index": 2, "leaf_value": 1.5}
+            }
+        }
+    ]
+}"#;
+
+/// Decoded Protocol Buffers message.
+struct Message(Vec<(u64, Value)>);
+
+enum Value {
+    Varint(u64),
+    Fixed32(u32),
+    Bytes(Vec<u8>),
+}
+
+fn decode(mut bytes: &[u8]) -> Result<Message> {
+    fn varint(bytes: &mut &[u8]) -> Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let Some((&b, rest)) = bytes.split_first() else {
+                bail!("unexpected end of varint");
+            };
+            *bytes = rest;
+            value |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("varint too long")
+    }
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes)?;
+        let value = match key & 7 {
+            0 => Value::Varint(varint(&mut bytes)?),
+            2 => {
+                let len = varint(&mut bytes)? as usize;
+                let (value, rest) = bytes.split_at(len);
+                bytes = rest;
+                Value::Bytes(value.to_vec())
+            }
+            5 => {
+                let (value, rest) = bytes.split_at(4);
+                bytes = rest;
+                Value::Fixed32(u32::from_le_bytes(value.try_into()?))
+            }
+            wire_type => bail!("unsupported wire type {wire_type}"),
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(Message(fields))
+}
+
+impl Message {
+    fn varints(&self, field: u64) -> Vec<u64> {
+        self.0
+            .iter()
+            .filter_map(|(f, v)| match v {
+                Value::Varint(v) if *f == field => Some(*v),
+                _ => None,
+            })
+            .collect()
+    }
+    fn varint(&self, field: u64) -> Option<u64> {
+        self.varints(field).first().copied()
+    }
+    fn floats(&self, field: u64) -> Vec<f32> {
+        self.0
+            .iter()
+            .filter_map(|(f, v)| match v {
+                Value::Fixed32(v) if *f == field => Some(f32::from_bits(*v)),
+                _ => None,
+            })
+            .collect()
+    }
+    fn bytes(&self, field: u64) -> Vec<&[u8]> {
+        self.0
+            .iter()
+            .filter_map(|(f, v)| match v {
+                Value::Bytes(v) if *f == field => Some(v.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+    fn strings(&self, field: u64) -> Vec<String> {
+        self.bytes(field)
+            .into_iter()
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+            .collect()
+    }
+    fn string(&self, field: u64) -> Option<String> {
+        self.strings(field).into_iter().next()
+    }
+    fn messages(&self, field: u64) -> Result<Vec<Message>> {
+        self.bytes(field).into_iter().map(decode).collect()
+    }
+}
+
+/// Attributes of `NodeProto`.
+struct Attributes(HashMap<String, Message>);
+
+impl Attributes {
+    fn new(node: &Message) -> Result<Self> {
+        let mut attrs = HashMap::new();
+        for a in node.messages(5)? {
+            attrs.insert(a.string(1).unwrap_or_default(), a);
+        }
+        Ok(Self(attrs))
+    }
+    fn ints(&self, name: &str) -> Vec<i64> {
+        let a = &self.0[name];
+        let mut values = a.varints(8);
+        values.extend(a.varint(3));
+        values.into_iter().map(|v| v as i64).collect()
+    }
+    fn floats(&self, name: &str) -> Vec<f32> {
+        self.0[name].floats(7)
+    }
+    fn strings(&self, name: &str) -> Vec<String> {
+        self.0[name].strings(9)
+    }
+    fn string(&self, name: &str) -> String {
+        self.0[name].string(4).unwrap_or_default()
+    }
+
+    /// Evaluate the tree ensemble for a row and return the sum of the weights of the reached leaves for each target id.
+    fn eval(&self, row: &[f32], target: &str) -> Vec<f32> {
+        let tree_ids = self.ints("nodes_treeids");
+        let node_ids = self.ints("nodes_nodeids");
+        let feature_ids = self.ints("nodes_featureids");
+        let modes = self.strings("nodes_modes");
+        let values = self.floats("nodes_values");
+        let true_ids = self.ints("nodes_truenodeids");
+        let false_ids = self.ints("nodes_falsenodeids");
+        let missing = self.ints("nodes_missing_value_tracks_true");
+        let index: HashMap<(i64, i64), usize> = tree_ids
+            .iter()
+            .zip(&node_ids)
+            .enumerate()
+            .map(|(i, (&t, &n))| ((t, n), i))
+            .collect();
+        let target_ids = self.ints(&format!("{target}_ids"));
+        let mut weights: HashMap<(i64, i64), Vec<(usize, f32)>> = HashMap::new();
+        for ((tree, node), (id, weight)) in self
+            .ints(&format!("{target}_treeids"))
+            .into_iter()
+            .zip(self.ints(&format!("{target}_nodeids")))
+            .zip(
+                target_ids
+                    .iter()
+                    .zip(self.floats(&format!("{target}_weights"))),
+            )
+        {
+            weights
+                .entry((tree, node))
+                .or_default()
+                .push((*id as usize, weight));
+        }
+        let mut trees = tree_ids.clone();
+        trees.dedup();
+        let mut sums = vec![0.0; target_ids.iter().max().map_or(0, |&id| id as usize + 1)];
+        for tree in trees {
+            let mut i = index[&(tree, 0)];
+            loop {
+                let x = row[feature_ids[i] as usize];
+                let cond = match modes[i].as_str() {
+                    "LEAF" => break,
+                    _ if x.is_nan() => missing[i] != 0,
+                    "BRANCH_LEQ" => x <= values[i],
+                    "BRANCH_EQ" => x == values[i],
+                    mode => panic!("unexpected mode {mode}"),
+                };
+                let next = if cond { true_ids[i] } else { false_ids[i] };
+                i = index[&(tree, next)];
+            }
+            for &(id, weight) in &weights[&(tree, node_ids[i])] {
+                sums[id] += weight;
+            }
+        }
+        sums
+    }
+
+    /// Evaluate `TreeEnsembleClassifier` and return the probabilities in the same way as ONNX Runtime.
+    ///
+    /// If there are two labels and only one class id, the scores are the probabilities of the second label.
+    fn probabilities(&self, row: &[f32]) -> Vec<f32> {
+        let num_label = self.ints("classlabels_int64s").len();
+        let scores = self.eval(row, "class");
+        match self.string("post_transform").as_str() {
+            "LOGISTIC" if num_label == 2 && scores.len() == 1 => {
+                let p = sigmoid(scores[0]);
+                vec![1.0 - p, p]
+            }
+            "LOGISTIC" => scores.into_iter().map(sigmoid).collect(),
+            "SOFTMAX" => {
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let exp: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+                let sum: f32 = exp.iter().sum();
+                exp.into_iter().map(|e| e / sum).collect()
+            }
+            post_transform => panic!("unexpected post_transform {post_transform}"),
+        }
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
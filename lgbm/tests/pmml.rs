@@ -0,0 +1,510 @@
+use anyhow::{Result, bail};
+use lgbm::{
+    model::{ModelDump, Node},
+    pmml::export,
+};
+use std::collections::HashMap;
+
+#[test]
+fn export_binary() -> Result<()> {
+    let model: ModelDump = serde_json::from_str(MODEL)?;
+    let pmml = export(&model)?;
+    assert!(pmml.contains(r#"<PMML xmlns="http://www.dmg.org/PMML-4_4" version="4.4">"#));
+    assert!(pmml.contains(r#"<DataField name="a&lt;1" optype="categorical" dataType="integer">"#));
+    assert!(pmml.contains(r#"<DataField name="b&amp;c" optype="continuous" dataType="double"/>"#));
+    assert!(pmml.contains(r#"<MiningModel functionName="classification">"#));
+    assert!(pmml.contains(r#"<Segmentation multipleModelMethod="modelChain">"#));
+    assert_eq!(pmml.matches("<TreeModel ").count(), 2);
+    assert!(pmml.contains(
+        r#"<SimpleSetPredicate field="a&lt;1" booleanOperator="isIn"><Array type="int" n="2">1 3</Array></SimpleSetPredicate>"#
+    ));
+    // Categorical splits send missing values right, and zero goes to the default child.
+    assert!(pmml.contains(r#"<Node id="0" defaultChild="2">"#));
+    assert!(pmml.contains(
+        r#"<CompoundPredicate booleanOperator="or"><SimplePredicate field="b&amp;c" operator="lessOrEqual" value="-0.5"/><SimplePredicate field="b&amp;c" operator="equal" value="0"/></CompoundPredicate>"#
+    ));
+    assert!(pmml.contains(
+        r#"<RegressionModel functionName="classification" normalizationMethod="logit">"#
+    ));
+    assert!(pmml.contains(r#"<NumericPredictor name="lgbmValue" coefficient="2.0"/>"#));
+    assert!(pmml.contains(r#"<OutputField name="probability(1)""#));
+    assert_eq!(pmml.matches("<Node ").count(), 10);
+    Ok(())
+}
+
+#[test]
+fn export_multiclass() -> Result<()> {
+    let mut model: ModelDump = serde_json::from_str(MODEL)?;
+    model.objective = Some("multiclass num_class:2".to_string());
+    model.num_class = 2;
+    model.num_tree_per_iteration = 2;
+    let pmml = export(&model)?;
+    assert!(pmml.contains(r#"normalizationMethod="softmax""#));
+    assert!(pmml.contains(r#"<OutputField name="lgbmValue(0)""#));
+    assert!(pmml.contains(r#"<NumericPredictor name="lgbmValue(1)" coefficient="1.0"/>"#));
+    assert_eq!(
+        pmml.matches(r#"<Segmentation multipleModelMethod="sum">"#)
+            .count(),
+        2
+    );
+
+    model.objective = Some("multiclassova num_class:2 sigmoid:1.5".to_string());
+    let pmml = export(&model)?;
+    assert!(!pmml.contains(r#"normalizationMethod="logit""#));
+    assert!(pmml.contains(r#"normalizationMethod="none""#));
+    assert!(pmml.contains(r#"<DerivedField name="sigmoidLgbmValue(1)""#));
+    Ok(())
+}
+
+#[test]
+fn evaluate() -> Result<()> {
+    let mut model: ModelDump = serde_json::from_str(MODEL)?;
+    for objective in [
+        "binary sigmoid:2",
+        "regression",
+        "regression sqrt",
+        "poisson",
+        "cross_entropy_lambda",
+        "multiclass num_class:2",
+        "multiclassova num_class:2 sigmoid:1.5",
+    ] {
+        let multiclass = objective.starts_with("multiclass");
+        model.objective = Some(objective.to_string());
+        model.num_class = if multiclass { 2 } else { 1 };
+        model.num_tree_per_iteration = model.num_class;
+        let pmml = parse(&export(&model)?)?;
+        for row in ROWS {
+            let raw: Vec<f64> = (0..model.num_tree_per_iteration)
+                .map(|k| {
+                    model
+                        .tree_info
+                        .iter()
+                        .skip(k)
+                        .step_by(model.num_tree_per_iteration)
+                        .map(|tree| leaf_value(&tree.tree_structure, row))
+                        .sum()
+                })
+                .collect();
+            let sigmoid = |x: f64, s: f64| 1.0 / (1.0 + (-s * x).exp());
+            let expected = match objective.split(' ').next().unwrap() {
+                "binary" => {
+                    let p = sigmoid(raw[0], 2.0);
+                    vec![1.0 - p, p]
+                }
+                "regression" if objective.contains("sqrt") => vec![raw[0] * raw[0].abs()],
+                "regression" => raw,
+                "poisson" => vec![raw[0].exp()],
+                "cross_entropy_lambda" => vec![raw[0].exp().ln_1p()],
+                "multiclass" => {
+                    let sum: f64 = raw.iter().map(|x| x.exp()).sum();
+                    raw.iter().map(|x| x.exp() / sum).collect()
+                }
+                _ => raw.iter().map(|&x| sigmoid(x, 1.5)).collect(),
+            };
+            let actual = eval_pmml(&pmml, row)?;
+            assert_eq!(actual.len(), expected.len(), "{objective} {row:?}");
+            for (a, e) in actual.iter().zip(&expected) {
+                assert!(
+                    (a - e).abs() < 1e-12,
+                    "{objective} {row:?}: {actual:?} != {expected:?}"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn export_regression() -> Result<()> {
+    let mut model: ModelDump = serde_json::from_str(MODEL)?;
+    model.objective = Some("regression".to_string());
+    let pmml = export(&model)?;
+    assert!(pmml.contains(r#"<MiningModel functionName="regression">"#));
+    assert!(!pmml.contains("modelChain"));
+
+    model.objective = Some("poisson".to_string());
+    let pmml = export(&model)?;
+    assert!(pmml.contains("modelChain"));
+    assert!(pmml.contains(r#"<Apply function="exp"><FieldRef field="lgbmValue"/></Apply>"#));
+
+    model.tree_info[1].tree_structure = Node::Leaf(serde_json::from_str(
+        r#"{"leaf_value": 1, "leaf_const": 1, "leaf_features": [0], "leaf_coeff": [1]}"#,
+    )?);
+    assert!(export(&model).is_err());
+    Ok(())
+}
+
+fn leaf_value(node: &Node, row: &[f64]) -> f64 {
+    match node {
+        Node::Split(split) => {
+            if split.goes_left(row[split.split_feature]) {
+                leaf_value(&split.left_child, row)
+            } else {
+                leaf_value(&split.right_child, row)
+            }
+        }
+        Node::Leaf(leaf) => leaf.leaf_value,
+    }
+}
+
+const ROWS: &[[f64; 2]] = &[
+    [0.0, 0.0],
+    [1.0, 0.0],
+    [3.0, f64::NAN],
+    [2.0, f64::NAN],
+    [f64::NAN, -1.0],
+    [-1.0, -0.4],
+    [2.0, 0.2],
+    [0.0, 0.3],
+    [0.0, -0.5],
+    [3.0, -0.6],
+    [1.0, 0.25],
+    [5.0, -2.0],
+];
+
+const MODEL: &str = r#"{
+    "name": "tree",
+    "version": "v4",
+    "num_class": 1,
+    "num_tree_per_iteration": 1,
+    "label_index": 0,
+    "max_feature_idx": 1,
+    "objective": "binary sigmoid:2",
+    "feature_names": ["a<1", "b&c"],
+    "feature_infos": {
+        "a<1": {"min_value": 0, "max_value": 3, "values": [0, 1, 2, 3]},
+        "b&c": {"min_value": -1.5, "max_value": 2.5, "values": []}
+    },
+    "tree_info": [
+        {
+            "tree_index": 0,
+            "num_leaves": 3,
+            "num_cat": 1,
+            "shrinkage": 1,
+            "tree_structure": {
+                "split_index": 0,
+                "split_feature": 0,
+                "split_gain": 1,
+                "threshold": "1||3",
+                "decision_type": "==",
+                "default_left": false,
+                "missing_type": "NaN",
+                "internal_value": 0,
+                "internal_weight": 0,
+                "internal_count": 0,
+                "left_child": {"leaf_index": 0, "leaf_value": 0.5},
+                "right_child": {
+                    "split_index": 1,
+                    "split_feature": 1,
+                    "split_gain": 1,
+                    "threshold": -0.5,
+                    "decision_type": "<=",
+                    "default_left": true,
+                    "missing_type": "Zero",
+                    "internal_value": 0,
+                    "internal_weight": 0,
+                    "internal_count": 0,
+                    "left_child": {"leaf_index": 1, "leaf_value": -0.25},
+                    "right_child": {"leaf_index": 2, "leaf_value": 0.75}
+                }
+            }
+        },
+        {
+            "tree_index": 1,
+            "num_leaves": 3,
+            "num_cat": 0,
+            "shrinkage": 1,
+            "tree_structure": {
+                "split_index": 0,
+                "split_feature": 1,
+                "split_gain": 1,
+                "threshold": 0.25,
+                "decision_type": "<=",
+                "default_left": false,
+                "missing_type": "NaN",
+                "internal_value": 0,
+                "internal_weight": 0,
+                "internal_count": 0,
+                "left_child": {
+                    "split_index": 1,
+                    "split_feature": 1,
+                    "split_gain": 1,
+                    "threshold": -1,
+                    "decision_type": "<=",
+                    "default_left": true,
+                    "missing_type": "None",
+                    "internal_value": 0,
+                    "internal_weight": 0,
+                    "internal_count": 0,
+                    "left_child": {"leaf_index": 0, "leaf_value": 0.125},
+                    "right_child": {"leaf_index": 1, "leaf_value": -0.125}
+                },
+                "right_child": {"leaf_index": 2, "leaf_value": 1.5}
+            }
+        }
+    ]
+}"#;
+
+/// Element of an XML document.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attrs: HashMap<String, String>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attr(&self, name: &str) -> &str {
+        self.attrs.get(name).map_or("", |v| v.as_str())
+    }
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+/// Parse the subset of XML written by `export`.
+fn parse(xml: &str) -> Result<Element> {
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+    let mut stack = vec![Element::default()];
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let text = rest[..start].trim();
+        if !text.is_empty() {
+            stack.last_mut().unwrap().text.push_str(&unescape(text));
+        }
+        let end = start + rest[start..].find('>').unwrap();
+        let tag = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let e = stack.pop().unwrap();
+            if e.name != name {
+                bail!("mismatched tag: {} != {name}", e.name);
+            }
+            stack.last_mut().unwrap().children.push(e);
+            continue;
+        }
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (name, mut attrs_str) = tag.split_once(' ').unwrap_or((tag, ""));
+        let mut e = Element {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        while let Some(eq) = attrs_str.find("=\"") {
+            let key = attrs_str[..eq].trim();
+            let value_end = eq + 2 + attrs_str[eq + 2..].find('"').unwrap();
+            e.attrs
+                .insert(key.to_string(), unescape(&attrs_str[eq + 2..value_end]));
+            attrs_str = &attrs_str[value_end + 1..];
+        }
+        if empty {
+            stack.last_mut().unwrap().children.push(e);
+        } else {
+            stack.push(e);
+        }
+    }
+    let mut root = stack.pop().unwrap();
+    if !stack.is_empty() || root.children.len() != 1 {
+        bail!("invalid document");
+    }
+    Ok(root.children.pop().unwrap())
+}
+
+/// Evaluate a PMML document and return the prediction or the probabilities of each category.
+fn eval_pmml(pmml: &Element, row: &[f64]) -> Result<Vec<f64>> {
+    let mut fields = HashMap::new();
+    for (field, value) in pmml
+        .child("DataDictionary")
+        .unwrap()
+        .children("DataField")
+        .zip(row)
+    {
+        fields.insert(field.attr("name").to_string(), *value);
+    }
+    eval_model(pmml.child("MiningModel").unwrap(), &mut fields)
+}
+
+fn eval_model(model: &Element, fields: &mut HashMap<String, f64>) -> Result<Vec<f64>> {
+    match model.name.as_str() {
+        "MiningModel" => {
+            let segmentation = model.child("Segmentation").unwrap();
+            let segments = segmentation.children("Segment");
+            match segmentation.attr("multipleModelMethod") {
+                "sum" => {
+                    let mut sum = 0.0;
+                    for segment in segments {
+                        sum += eval_model(segment.children.last().unwrap(), fields)?[0];
+                    }
+                    Ok(vec![sum])
+                }
+                "modelChain" => {
+                    let mut result = Vec::new();
+                    for segment in segments {
+                        let model = segment.children.last().unwrap();
+                        result = eval_model(model, fields)?;
+                        if let Some(output) = model.child("Output") {
+                            for field in output.children("OutputField") {
+                                if field.attr("feature") == "predictedValue" {
+                                    fields.insert(field.attr("name").to_string(), result[0]);
+                                }
+                            }
+                        }
+                    }
+                    Ok(result)
+                }
+                method => bail!("unexpected multipleModelMethod {method}"),
+            }
+        }
+        "TreeModel" => {
+            let mut node = model.child("Node").unwrap();
+            loop {
+                let children: Vec<&Element> = node.children("Node").collect();
+                if children.is_empty() {
+                    return Ok(vec![node.attr("score").parse()?]);
+                }
+                let mut next = None;
+                for child in &children {
+                    match eval_predicate(&child.children[0], fields)? {
+                        Some(true) => {
+                            next = Some(*child);
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            let id = node.attr("defaultChild");
+                            next = children.iter().find(|c| c.attr("id") == id).copied();
+                            break;
+                        }
+                    }
+                }
+                node = next.unwrap();
+            }
+        }
+        "RegressionModel" => {
+            if let Some(transformations) = model.child("LocalTransformations") {
+                for field in transformations.children("DerivedField") {
+                    let value = eval_expr(&field.children[0], fields)?;
+                    fields.insert(field.attr("name").to_string(), value);
+                }
+            }
+            let mut categories = Vec::new();
+            let mut ys = Vec::new();
+            for table in model.children("RegressionTable") {
+                let mut y: f64 = table.attr("intercept").parse()?;
+                for p in table.children("NumericPredictor") {
+                    y += p.attr("coefficient").parse::<f64>()? * fields[p.attr("name")];
+                }
+                categories.push(table.attr("targetCategory").parse().unwrap_or(0));
+                ys.push(y);
+            }
+            let ps = match model.attr("normalizationMethod") {
+                "" | "none" => ys,
+                "logit" if ys.len() == 2 => {
+                    let p = 1.0 / (1.0 + (-ys[0]).exp());
+                    vec![p, 1.0 - p]
+                }
+                "softmax" => {
+                    let sum: f64 = ys.iter().map(|y| y.exp()).sum();
+                    ys.iter().map(|y| y.exp() / sum).collect()
+                }
+                method => bail!("unexpected normalizationMethod {method}"),
+            };
+            let mut result = vec![0.0; ps.len()];
+            for (category, p) in categories.into_iter().zip(ps) {
+                result[category] = p;
+            }
+            Ok(result)
+        }
+        name => bail!("unexpected model {name}"),
+    }
+}
+
+/// Evaluate a predicate. `None` means that the result is unknown because of a missing value.
+fn eval_predicate(p: &Element, fields: &HashMap<String, f64>) -> Result<Option<bool>> {
+    let value = || fields[p.attr("field")];
+    Ok(match p.name.as_str() {
+        "True" => Some(true),
+        "SimplePredicate" => {
+            let x = value();
+            let v: f64 = p.attr("value").parse()?;
+            match p.attr("operator") {
+                _ if x.is_nan() => None,
+                "lessOrEqual" => Some(x <= v),
+                "equal" => Some(x == v),
+                "notEqual" => Some(x != v),
+                op => bail!("unexpected operator {op}"),
+            }
+        }
+        "SimpleSetPredicate" => {
+            let x = value();
+            let array = p.child("Array").unwrap();
+            let values = array
+                .text
+                .split_whitespace()
+                .map(|v| v.parse())
+                .collect::<Result<Vec<f64>, _>>()?;
+            assert_eq!(p.attr("booleanOperator"), "isIn");
+            assert_eq!(array.attr("n").parse::<usize>()?, values.len());
+            (!x.is_nan()).then(|| values.contains(&x))
+        }
+        "CompoundPredicate" => {
+            let results = p
+                .children
+                .iter()
+                .map(|c| eval_predicate(c, fields))
+                .collect::<Result<Vec<_>>>()?;
+            let (absorbing, identity) = match p.attr("booleanOperator") {
+                "or" => (true, false),
+                "and" => (false, true),
+                op => bail!("unexpected booleanOperator {op}"),
+            };
+            if results.contains(&Some(absorbing)) {
+                Some(absorbing)
+            } else if results.contains(&None) {
+                None
+            } else {
+                Some(identity)
+            }
+        }
+        name => bail!("unexpected predicate {name}"),
+    })
+}
+
+fn eval_expr(e: &Element, fields: &HashMap<String, f64>) -> Result<f64> {
+    Ok(match e.name.as_str() {
+        "Constant" => e.text.parse()?,
+        "FieldRef" => fields[e.attr("field")],
+        "Apply" => {
+            let args = e
+                .children
+                .iter()
+                .map(|c| eval_expr(c, fields))
+                .collect::<Result<Vec<_>>>()?;
+            match (e.attr("function"), args.as_slice()) {
+                ("exp", [x]) => x.exp(),
+                ("ln", [x]) => x.ln(),
+                ("abs", [x]) => x.abs(),
+                ("+", [x, y]) => x + y,
+                ("*", [x, y]) => x * y,
+                ("/", [x, y]) => x / y,
+                (function, _) => bail!("unexpected function {function}"),
+            }
+        }
+        name => bail!("unexpected expression {name}"),
+    })
+}
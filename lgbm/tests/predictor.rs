@@ -0,0 +1,145 @@
+mod common;
+
+use anyhow::Result;
+use common::{make_booster, make_features, parameters};
+use lgbm::{
+    Booster, FeatureImportanceType, PredictType, parameters::Objective, predictor::Predictor,
+};
+
+#[test]
+fn predict_binary() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    let b = make_booster(&p, |x| (x[0] > 0.3 || x[2] < -1.0) as i32 as f64)?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_regression() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    let b = make_booster(&p, |x| x[1] * 2.0 + x[2])?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_regression_sqrt() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    p.push("reg_sqrt", true);
+    let b = make_booster(&p, |x| x[1] * 2.0 + x[2])?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_poisson() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Poisson);
+    let b = make_booster(&p, |x| x[1])?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_multiclass() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3) as f64)?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_categorical() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("categorical_feature", [1]);
+    p.push("min_data_per_group", 5);
+    p.push("cat_smooth", 1.0);
+    let b = make_booster(&p, |x| (x[1] as i32 % 3 == 1) as i32 as f64)?;
+    assert!(
+        b.save_model_to_string(0, None, FeatureImportanceType::Split)?
+            .to_str()?
+            .contains("num_cat=1")
+    );
+    check_predict(&b)
+}
+
+#[test]
+fn predict_missing_zero() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    p.push("zero_as_missing", true);
+    let b = make_booster(&p, |x| x[1] + x[2])?;
+    check_predict(&b)
+}
+
+#[test]
+fn predict_linear_tree() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Regression);
+    p.push("linear_tree", true);
+    let b = make_booster(&p, |x| x[1] * 2.0 + x[2])?;
+    check_predict(&b)
+}
+
+#[test]
+fn predictor_invalid() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    let b = make_booster(&p, |x| (x[0] > 0.3) as i32 as f64)?;
+    let model = b.save_model_to_string(0, None, FeatureImportanceType::Split)?;
+    let predictor = Predictor::from_string(model.to_str()?)?;
+    assert!(
+        predictor
+            .predict(&[0.0; 4], PredictType::Normal, 0, None)
+            .is_err()
+    );
+    assert!(
+        predictor
+            .predict(&[0.0; 3], PredictType::Contrib, 0, None)
+            .is_err()
+    );
+    assert!(Predictor::from_string("").is_err());
+    Ok(())
+}
+
+fn check_predict(b: &Booster) -> Result<()> {
+    let model = b.save_model_to_string(0, None, FeatureImportanceType::Split)?;
+    let predictor = Predictor::from_string(model.to_str()?)?;
+    assert_eq!(predictor.num_class(), b.get_num_classes()?);
+    assert_eq!(predictor.num_feature(), 3);
+    assert_eq!(predictor.num_iteration(), b.get_current_iteration()?);
+
+    let features = make_features(200, 1);
+    for predict_type in [
+        PredictType::Normal,
+        PredictType::RawScore,
+        PredictType::LeafIndex,
+    ] {
+        for (start_iteration, num_iteration) in [(0, None), (3, Some(4)), (8, Some(100))] {
+            let expected = b.predict_for_mat(
+                &features,
+                predict_type,
+                start_iteration,
+                num_iteration,
+                &parameters(),
+            )?;
+            let actual = predictor.predict(
+                features.as_slice(),
+                predict_type,
+                start_iteration,
+                num_iteration,
+            )?;
+            assert_eq!(
+                actual.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+                expected
+                    .values()
+                    .iter()
+                    .map(|x| x.to_bits())
+                    .collect::<Vec<_>>(),
+                "{predict_type:?} {start_iteration} {num_iteration:?}"
+            );
+        }
+    }
+    Ok(())
+}
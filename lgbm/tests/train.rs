@@ -0,0 +1,373 @@
+use anyhow::Result;
+use lgbm::{
+    Booster, Dataset, Field, MatBuf, Parameters, Prediction,
+    mat::RowMajor,
+    parameters::{Metric, Objective, Verbosity},
+    train::{
+        self, Callback, CallbackEnv, Custom, EarlyStopping, EvalData, EvalMetric, LogEvaluation,
+        ResetParameter, train, train_custom,
+    },
+};
+use std::{
+    io::Write,
+    ops::ControlFlow,
+    sync::{Arc, Mutex},
+};
+
+#[test]
+fn train_history() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", [Metric::BinaryLogloss, Metric::Auc]);
+
+    let d = make_dataset(100, None, &p)?;
+    let v = make_dataset(50, Some(&d), &p)?;
+    let r = train(&p, d.clone(), &[("train", d), ("valid", v)], 10, &mut [])?;
+    assert_eq!(r.booster.get_current_iteration()?, 10);
+    assert_eq!(r.best_iteration, None);
+    for data_name in ["train", "valid"] {
+        let h = &r.history[data_name];
+        assert_eq!(h["binary_logloss"].len(), 10);
+        assert_eq!(h["auc"].len(), 10);
+    }
+    Ok(())
+}
+
+#[test]
+fn train_early_stopping() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", Metric::BinaryLogloss);
+
+    let d = make_dataset(100, None, &p)?;
+    let v = make_dataset(50, Some(&d), &p)?;
+    let mut early_stopping = EarlyStopping::new(3).min_delta(1.0);
+    let r = train(&p, d, &[("valid", v)], 100, &mut [&mut early_stopping])?;
+    assert_eq!(r.best_iteration, Some(1));
+    assert_eq!(r.booster.get_current_iteration()?, 4);
+    assert_eq!(r.history["valid"]["binary_logloss"].len(), 4);
+    Ok(())
+}
+
+#[test]
+fn train_log_evaluation() -> Result<()> {
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", Metric::BinaryLogloss);
+
+    let d = make_dataset(100, None, &p)?;
+    let v = make_dataset(50, Some(&d), &p)?;
+    let buf = SharedBuf::default();
+    let mut log = LogEvaluation::new(2).writer(buf.clone());
+    train(&p, d, &[("valid", v)], 5, &mut [&mut log])?;
+    let out = String::from_utf8(buf.0.lock().unwrap().clone())?;
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("[2]\tvalid's binary_logloss: "));
+    assert!(lines[2].starts_with("[5]\t"));
+    Ok(())
+}
+
+#[test]
+fn train_callback() -> Result<()> {
+    struct StopAt(usize);
+    impl Callback for StopAt {
+        fn before_iteration(&mut self, env: &mut CallbackEnv) -> lgbm::Result<()> {
+            assert!(env.evals.is_empty());
+            Ok(())
+        }
+        fn after_iteration(&mut self, env: &mut CallbackEnv) -> lgbm::Result<ControlFlow<()>> {
+            if env.iteration + 1 == self.0 {
+                Ok(ControlFlow::Break(()))
+            } else {
+                Ok(ControlFlow::Continue(()))
+            }
+        }
+    }
+
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    let d = make_dataset(100, None, &p)?;
+    let r = train(&p, d, &[], 10, &mut [&mut StopAt(3)])?;
+    assert_eq!(r.booster.get_current_iteration()?, 3);
+    assert!(r.history.is_empty());
+    Ok(())
+}
+
+#[test]
+fn train_reset_parameter() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", Metric::BinaryLogloss);
+
+    let d = make_dataset(100, None, &p)?;
+    let mut iterations = Vec::new();
+    let mut schedule = ResetParameter(|iteration| {
+        iterations.push(iteration);
+        let mut p = Parameters::new();
+        if iteration >= 2 {
+            p.push("learning_rate", 1e-10);
+        }
+        p
+    });
+    let r = train(&p, d.clone(), &[("train", d)], 5, &mut [&mut schedule])?;
+    let h = &r.history["train"]["binary_logloss"];
+    assert!(h[1] < h[0]);
+    assert!((h[4] - h[1]).abs() < 1e-6);
+    assert_eq!(iterations, [0, 1, 2, 3, 4]);
+    Ok(())
+}
+
+#[test]
+fn reset_parameter_invalid() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    let d = make_dataset(100, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    let mut p = Parameters::new();
+    p.push("num_class", 3);
+    assert!(b.reset_parameter(&p).is_err());
+    Ok(())
+}
+
+struct BinaryLogloss;
+impl train::Objective for BinaryLogloss {
+    fn grad_hess(&self, preds: &Prediction, dataset: &Dataset, grad: &mut [f32], hess: &mut [f32]) {
+        let label = dataset.get_field(Field::LABEL).unwrap();
+        for row in 0..preds.num_data() {
+            let p = 1.0 / (1.0 + (-preds[row]).exp());
+            grad[row] = (p - label[row] as f64) as f32;
+            hess[row] = (p * (1.0 - p)) as f32;
+        }
+    }
+}
+
+#[test]
+fn train_custom_objective() -> Result<()> {
+    let mut p = parameters();
+    p.push("metric", Metric::Auc);
+    let d = make_dataset(100, None, &p)?;
+    let custom = Custom {
+        objective: Some(&BinaryLogloss),
+        ..Default::default()
+    };
+    let r = train_custom(&p, d.clone(), &[("train", d)], 10, &mut [], custom)?;
+    assert_eq!(r.booster.get_current_iteration()?, 10);
+    assert!(r.history["train"]["auc"][9] > 0.9);
+    Ok(())
+}
+
+/// Same as the built-in `multiclass` objective.
+struct Softmax;
+impl train::Objective for Softmax {
+    fn grad_hess(&self, preds: &Prediction, dataset: &Dataset, grad: &mut [f32], hess: &mut [f32]) {
+        let label = dataset.get_field(Field::LABEL).unwrap();
+        let num_data = preds.num_data();
+        let num_class = preds.num_class();
+        let factor = num_class as f64 / (num_class - 1) as f64;
+        for row in 0..num_data {
+            let max = (0..num_class)
+                .map(|class| preds[[row, class]])
+                .fold(f64::NEG_INFINITY, f64::max);
+            let exp: Vec<f64> = (0..num_class)
+                .map(|class| (preds[[row, class]] - max).exp())
+                .collect();
+            let sum: f64 = exp.iter().sum();
+            for class in 0..num_class {
+                let p = exp[class] / sum;
+                let y = (label[row] as usize == class) as i32 as f64;
+                grad[class * num_data + row] = (p - y) as f32;
+                hess[class * num_data + row] = (factor * p * (1.0 - p)) as f32;
+            }
+        }
+    }
+}
+
+struct MultiLogloss;
+impl EvalMetric for MultiLogloss {
+    fn name(&self) -> &str {
+        "custom_multi_logloss"
+    }
+    fn higher_better(&self) -> bool {
+        false
+    }
+    fn eval(&self, preds: &Prediction, data: &EvalData) -> f64 {
+        let loss: f64 = (0..preds.num_data())
+            .map(|row| {
+                let sum: f64 = (0..preds.num_class())
+                    .map(|class| preds[[row, class]].exp())
+                    .sum();
+                sum.ln() - preds[[row, data.label[row] as usize]]
+            })
+            .sum();
+        loss / preds.num_data() as f64
+    }
+}
+
+#[test]
+fn train_custom_objective_multiclass() -> Result<()> {
+    let mut p = parameters();
+    p.push("num_class", 3);
+    p.push("boost_from_average", false);
+
+    let d = make_dataset_multiclass(150, 3, &p)?;
+    let custom = Custom {
+        objective: Some(&Softmax),
+        metrics: &[&MultiLogloss],
+    };
+    let r = train_custom(&p, d.clone(), &[("train", d.clone())], 10, &mut [], custom)?;
+    let actual = &r.history["train"]["custom_multi_logloss"];
+
+    p.push("objective", Objective::Multiclass);
+    p.push("metric", Metric::MultiLogloss);
+    let r = train(&p, d.clone(), &[("train", d)], 10, &mut [])?;
+    let expected = &r.history["train"]["multi_logloss"];
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}");
+    }
+    assert!(expected[9] < expected[0]);
+    Ok(())
+}
+
+struct Accuracy;
+impl EvalMetric for Accuracy {
+    fn name(&self) -> &str {
+        "accuracy"
+    }
+    fn higher_better(&self) -> bool {
+        true
+    }
+    fn eval(&self, preds: &Prediction, data: &EvalData) -> f64 {
+        let n = (0..preds.num_data())
+            .filter(|&row| (preds[row] > 0.5) == (data.label[row] > 0.5))
+            .count();
+        n as f64 / preds.num_data() as f64
+    }
+}
+
+#[test]
+fn train_custom_metric() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Binary);
+    p.push("metric", Metric::BinaryLogloss);
+
+    let d = make_dataset(100, None, &p)?;
+    let v = make_dataset(50, Some(&d), &p)?;
+    let custom = Custom {
+        metrics: &[&Accuracy],
+        ..Default::default()
+    };
+    let mut early_stopping = EarlyStopping::new(3);
+    let r = train_custom(
+        &p,
+        d,
+        &[("valid", v)],
+        20,
+        &mut [&mut early_stopping],
+        custom,
+    )?;
+    let h = &r.history["valid"];
+    assert_eq!(h["binary_logloss"].len(), h["accuracy"].len());
+    assert!(h["accuracy"].iter().all(|&a| (0.0..=1.0).contains(&a)));
+    Ok(())
+}
+
+/// Accuracy that counts ties as errors, the same as `multi_error`.
+struct MulticlassAccuracy;
+impl EvalMetric for MulticlassAccuracy {
+    fn name(&self) -> &str {
+        "multi_accuracy"
+    }
+    fn higher_better(&self) -> bool {
+        true
+    }
+    fn eval(&self, preds: &Prediction, data: &EvalData) -> f64 {
+        let n = (0..preds.num_data())
+            .filter(|&row| {
+                let label = data.label[row] as usize;
+                (0..preds.num_class())
+                    .all(|class| class == label || preds[[row, class]] < preds[[row, label]])
+            })
+            .count();
+        n as f64 / preds.num_data() as f64
+    }
+}
+
+#[test]
+fn train_custom_metric_multiclass() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Multiclass);
+    p.push("num_class", 3);
+    p.push("metric", Metric::MultiError);
+
+    let d = make_dataset_multiclass(150, 3, &p)?;
+    let custom = Custom {
+        metrics: &[&MulticlassAccuracy],
+        ..Default::default()
+    };
+    let r = train_custom(&p, d.clone(), &[("train", d)], 5, &mut [], custom)?;
+    let h = &r.history["train"];
+    assert_eq!(h["multi_accuracy"].len(), 5);
+    for (accuracy, error) in h["multi_accuracy"].iter().zip(&h["multi_error"]) {
+        assert!((accuracy + error - 1.0).abs() < 1e-9);
+    }
+    assert_eq!(h["multi_accuracy"][4], 1.0);
+    Ok(())
+}
+
+#[test]
+fn update_one_iter_custom_invalid_len() -> Result<()> {
+    let mut p = parameters();
+    p.push("objective", Objective::Custom);
+    let d = make_dataset(100, None, &p)?;
+    let mut b = Booster::new(d, &p)?;
+    assert!(b.update_one_iter_custom(&[0.0; 99], &[0.0; 100]).is_err());
+    assert!(b.update_one_iter_custom(&[0.0; 100], &[0.0; 99]).is_err());
+    Ok(())
+}
+
+fn make_features(num_row: usize) -> MatBuf<f64, RowMajor> {
+    MatBuf::from_rows((0..num_row).map(|x| [(x % 2) as f64 + 1.0, x as f64]))
+}
+fn make_labels(num_row: usize) -> Vec<f32> {
+    (0..num_row).map(|x| (x % 2) as f32).collect()
+}
+fn make_dataset(
+    num_row: usize,
+    reference: Option<&Dataset>,
+    p: &Parameters,
+) -> Result<Arc<Dataset>> {
+    let mut d = Dataset::from_mat(make_features(num_row), reference, p)?;
+    d.set_field(Field::LABEL, &make_labels(num_row))?;
+    Ok(Arc::new(d))
+}
+
+fn make_dataset_multiclass(
+    num_row: usize,
+    num_class: usize,
+    p: &Parameters,
+) -> Result<Arc<Dataset>> {
+    let features = MatBuf::from_rows((0..num_row).map(|x| [(x % num_class) as f64, x as f64]));
+    let labels: Vec<f32> = (0..num_row).map(|x| (x % num_class) as f32).collect();
+    let mut d = Dataset::from_mat(features, None, p)?;
+    d.set_field(Field::LABEL, &labels)?;
+    Ok(Arc::new(d))
+}
+
+fn parameters() -> Parameters {
+    let mut p = Parameters::new();
+    p.push("verbosity", Verbosity::Fatal);
+    p
+}
@@ -0,0 +1,48 @@
+//! Generate standalone Rust or C source code from a LightGBM model.
+//!
+//! ```sh
+//! lgbm-codegen <MODEL> [--lang rust|c] [--output <FILE>]
+//! ```
+//!
+//! `MODEL` is a model file saved by `Booster::save_model` or the JSON returned by `Booster::dump_model`.
+
+use anyhow::{Context, Result, bail};
+use lgbm::codegen::{Language, generate_from_model_string};
+use std::{fs, path::PathBuf};
+
+fn main() -> Result<()> {
+    let mut model = None;
+    let mut language = Language::Rust;
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lang" => {
+                language = match args.next().as_deref() {
+                    Some("rust") => Language::Rust,
+                    Some("c") => Language::C,
+                    lang => bail!("unknown language: {lang:?}"),
+                }
+            }
+            "--output" | "-o" => {
+                output = Some(PathBuf::from(args.next().context("missing output")?))
+            }
+            "--help" | "-h" => {
+                println!("usage: lgbm-codegen <MODEL> [--lang rust|c] [--output <FILE>]");
+                return Ok(());
+            }
+            _ if model.is_none() => model = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument: {arg}"),
+        }
+    }
+    let model = model.context("usage: lgbm-codegen <MODEL> [--lang rust|c] [--output <FILE>]")?;
+    let text = fs::read_to_string(&model).with_context(|| format!("failed to read {model:?}"))?;
+    let code = generate_from_model_string(&text, language)?;
+    match output {
+        Some(output) => {
+            fs::write(&output, code).with_context(|| format!("failed to write {output:?}"))?
+        }
+        None => print!("{code}"),
+    }
+    Ok(())
+}